@@ -1,12 +1,53 @@
 #!/usr/bin/env bash
-[[ $# -eq 0 ]] && echo "Usage: ./advent.sh day"
+RED="\e[31m"
+GREEN="\e[32m"
+
+[[ $# -eq 0 ]] && echo "Usage: ./advent.sh day | ./advent.sh watch day"
+
+if [ "$1" == "watch" ]; then
+  DIR=$(printf "day%02d" $2)
+  [ ! -d $DIR ] && echo "No directory for day $2!" && exit 1
+  echo "🦀 WATCHING DAY $2"
+  cd $DIR
+
+  # GNU stat takes -c %Y for mtimes, BSD and macOS stat take -f %m
+  if stat -c %Y . > /dev/null 2>&1; then
+    MTIME="stat -c %Y"
+  else
+    MTIME="stat -f %m"
+  fi
+
+  LAST=""
+  PREVIOUS=""
+  while true; do
+    STAMP=$($MTIME src/*.rs input.txt Cargo.toml 2> /dev/null)
+    if [ "$STAMP" != "$LAST" ]; then
+      LAST=$STAMP
+      printf "\n[%s] rebuilding\n" "$(date +%T)"
+      # Only stdout is diffed, warnings and errors go straight to the terminal
+      if CURRENT=$(cargo run --release --quiet); then
+        if [ -z "$PREVIOUS" ]; then
+          echo "$CURRENT"
+        elif [ "$CURRENT" == "$PREVIOUS" ]; then
+          echo "$CURRENT"
+          echo "(answers unchanged)"
+        else
+          diff <(echo "$PREVIOUS") <(echo "$CURRENT") \
+            --old-line-format="-%L" --new-line-format="+%L" --unchanged-line-format=" %L"
+        fi
+        PREVIOUS=$CURRENT
+      else
+        [ -n "$CURRENT" ] && echo "$CURRENT"
+        printf "$RED%s\e[0m\n" "build or run failed"
+      fi
+    fi
+    sleep 1
+  done
+fi
 
 echo "🦀 DAY $1"
 DIR=$(printf "day%02d" $1)
 
-RED="\e[31m"
-GREEN="\e[32m"
-
 if [ ! -d $DIR ]; then
   URL=https://adventofcode.com/2021/day/$1
   cp -r template $DIR
@@ -1,12 +1,262 @@
+use std::io::{self, BufRead};
+
+// Comparing two overlapping windows of `size` depths only needs the depth
+// that leaves and the depth that enters, so the ring holds `size` depths.
+struct Ring {
+    buffer: Vec<u32>,
+    size: usize,
+    oldest: usize,
+}
+
+impl Ring {
+    fn new(size: usize) -> Self {
+        assert!(size > 0, "window size must be positive");
+        Self {
+            buffer: Vec::with_capacity(size),
+            size,
+            oldest: 0,
+        }
+    }
+
+    // Returns the depth `size` readings before `depth`, once there is one.
+    fn push(&mut self, depth: u32) -> Option<u32> {
+        if self.buffer.len() < self.size {
+            self.buffer.push(depth);
+            return None;
+        }
+        let leaving = std::mem::replace(&mut self.buffer[self.oldest], depth);
+        self.oldest = (self.oldest + 1) % self.size;
+        Some(leaving)
+    }
+}
+
+fn increases(depths: impl IntoIterator<Item = u32>, size: usize) -> usize {
+    let mut ring = Ring::new(size);
+    depths
+        .into_iter()
+        .filter(|&depth| matches!(ring.push(depth), Some(leaving) if leaving < depth))
+        .count()
+}
+
+fn read_depths(reader: impl BufRead) -> impl Iterator<Item = io::Result<u32>> {
+    reader
+        .lines()
+        .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .map(|line| {
+            let line = line?;
+            line.trim()
+                .parse()
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, line))
+        })
+}
+
+// Counts as it reads, stopping at the first line that is not a depth.
+fn stream_increases(reader: impl BufRead, size: usize) -> io::Result<usize> {
+    let mut error = None;
+    let depths = read_depths(reader).map_while(|depth| depth.map_err(|e| error = Some(e)).ok());
+    let count = increases(depths, size);
+    match error {
+        Some(e) => Err(e),
+        None => Ok(count),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Run {
+    start: usize,
+    len: usize,
+}
+
+// `at` is the index of the reading that completes the drop.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct DepthDrop {
+    at: usize,
+    amount: u32,
+}
+
+#[derive(Debug)]
+struct Report {
+    window: usize,
+    readings: usize,
+    increases: usize,
+    window_increases: usize,
+    longest_increase: Option<Run>,
+    plateaus: Vec<Run>,
+    largest_drop: Option<DepthDrop>,
+    largest_window_drop: Option<DepthDrop>,
+}
+
+fn largest(current: Option<DepthDrop>, at: usize, from: u32, to: u32) -> Option<DepthDrop> {
+    if to >= from || matches!(current, Some(drop) if drop.amount >= from - to) {
+        return current;
+    }
+    Some(DepthDrop {
+        at,
+        amount: from - to,
+    })
+}
+
+impl Report {
+    fn new(depths: impl IntoIterator<Item = u32>, window: usize) -> Self {
+        let mut report = Report {
+            window,
+            readings: 0,
+            increases: 0,
+            window_increases: 0,
+            longest_increase: None,
+            plateaus: vec![],
+            largest_drop: None,
+            largest_window_drop: None,
+        };
+        let mut previous = Ring::new(1);
+        let mut windowed = Ring::new(window);
+        let mut rising = Run { start: 0, len: 1 };
+        let mut level = Run { start: 0, len: 1 };
+
+        for (i, depth) in depths.into_iter().enumerate() {
+            report.readings += 1;
+            if let Some(leaving) = windowed.push(depth) {
+                report.window_increases += (leaving < depth) as usize;
+                report.largest_window_drop = largest(report.largest_window_drop, i, leaving, depth);
+            }
+            let last = match previous.push(depth) {
+                Some(last) => last,
+                None => continue,
+            };
+            report.largest_drop = largest(report.largest_drop, i, last, depth);
+
+            if last < depth {
+                report.increases += 1;
+                rising.len += 1;
+                if report
+                    .longest_increase
+                    .is_none_or(|run| run.len < rising.len)
+                {
+                    report.longest_increase = Some(rising);
+                }
+            } else {
+                rising = Run { start: i, len: 1 };
+            }
+
+            if last == depth {
+                level.len += 1;
+            } else {
+                if level.len > 1 {
+                    report.plateaus.push(level);
+                }
+                level = Run { start: i, len: 1 };
+            }
+        }
+        if level.len > 1 {
+            report.plateaus.push(level);
+        }
+        report
+    }
+
+    fn table(&self) -> String {
+        let run = |run: Option<Run>| match run {
+            Some(Run { start, len }) => format!("{} readings from index {}", len, start),
+            None => "-".to_owned(),
+        };
+        let drop = |drop: Option<DepthDrop>| match drop {
+            Some(DepthDrop { at, amount }) => format!("{} at index {}", amount, at),
+            None => "-".to_owned(),
+        };
+        let plateaus = self
+            .plateaus
+            .iter()
+            .map(|p| format!("{}@{}", p.len, p.start))
+            .collect::<Vec<_>>()
+            .join(", ");
+        [
+            ("readings", self.readings.to_string()),
+            ("increases", self.increases.to_string()),
+            (
+                "window increases",
+                format!("{} (window {})", self.window_increases, self.window),
+            ),
+            ("longest increase", run(self.longest_increase)),
+            (
+                "plateaus",
+                format!("{} [{}]", self.plateaus.len(), plateaus),
+            ),
+            ("largest drop", drop(self.largest_drop)),
+            ("largest window drop", drop(self.largest_window_drop)),
+        ]
+        .iter()
+        .map(|(name, value)| format!("{:<20} {}\n", name, value))
+        .collect()
+    }
+
+    fn json(&self) -> String {
+        let run = |run: Option<Run>| match run {
+            Some(Run { start, len }) => format!("{{\"start\":{},\"len\":{}}}", start, len),
+            None => "null".to_owned(),
+        };
+        let drop = |drop: Option<DepthDrop>| match drop {
+            Some(DepthDrop { at, amount }) => format!("{{\"at\":{},\"amount\":{}}}", at, amount),
+            None => "null".to_owned(),
+        };
+        let plateaus = self
+            .plateaus
+            .iter()
+            .map(|&p| run(Some(p)))
+            .collect::<Vec<_>>()
+            .join(",");
+        format!(
+            "{{\"window\":{},\"readings\":{},\"increases\":{},\"window_increases\":{},\
+             \"longest_increase\":{},\"plateaus\":[{}],\"largest_drop\":{},\
+             \"largest_window_drop\":{}}}",
+            self.window,
+            self.readings,
+            self.increases,
+            self.window_increases,
+            run(self.longest_increase),
+            plateaus,
+            drop(self.largest_drop),
+            drop(self.largest_window_drop),
+        )
+    }
+}
+
 fn part1(data: &[u32]) -> usize {
-    data.windows(2).filter(|x| x.first() < x.last()).count()
+    increases(data.iter().copied(), 1)
 }
 
 fn part2(data: &[u32]) -> usize {
-    data.windows(4).filter(|x| x.first() < x.last()).count()
+    increases(data.iter().copied(), 3)
 }
 
 fn main() {
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    // `cargo run -- --report [--json]` analyses the trend of the puzzle input
+    if args.iter().any(|arg| arg == "--report") {
+        let report = Report::new(
+            include_str!("../input.txt")
+                .lines()
+                .map(|x| x.parse().unwrap()),
+            3,
+        );
+        if args.iter().any(|arg| arg == "--json") {
+            println!("{}", report.json());
+        } else {
+            print!("{}", report.table());
+        }
+        return;
+    }
+    // `cargo run -- <window size> < sonar.log` streams depths from stdin
+    if let Some(size) = args.first() {
+        let size = match size.parse() {
+            Ok(size) if size > 0 => size,
+            _ => {
+                eprintln!("Usage: <window size> < sonar.log, with a window size of at least 1");
+                return;
+            }
+        };
+        let count = stream_increases(io::stdin().lock(), size).unwrap();
+        println!("Increases: {}", count);
+        return;
+    }
     let data: Vec<_> = include_str!("../input.txt")
         .lines()
         .map(|x| x.parse().unwrap())
@@ -14,3 +264,72 @@ fn main() {
     println!("Part 1: {}", part1(&data));
     println!("Part 2: {}", part2(&data));
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const EXAMPLE: [u32; 10] = [199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+
+    #[test]
+    fn test_ring() {
+        let mut ring = Ring::new(3);
+        let leaving: Vec<_> = (1..=6).map(|depth| ring.push(depth)).collect();
+        assert_eq!(leaving, [None, None, None, Some(1), Some(2), Some(3)]);
+    }
+
+    #[test]
+    fn test_increases() {
+        assert_eq!(increases(EXAMPLE, 1), 7);
+        assert_eq!(increases(EXAMPLE, 3), 5);
+        assert_eq!(increases(EXAMPLE, 10), 0);
+    }
+
+    #[test]
+    fn test_stream_increases() {
+        let log = "199\n200\n\n208\n210\n  \n200\n207\n240\n269\n260\n263\n";
+        assert_eq!(stream_increases(log.as_bytes(), 1).unwrap(), 7);
+        assert_eq!(stream_increases(log.as_bytes(), 3).unwrap(), 5);
+        let error = stream_increases("199\n2oo\n208\n".as_bytes(), 1).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(error.to_string(), "2oo");
+    }
+
+    #[test]
+    fn test_report() {
+        let report = Report::new(EXAMPLE, 3);
+        assert_eq!(report.readings, 10);
+        assert_eq!(report.increases, 7);
+        assert_eq!(report.window_increases, 5);
+        // Two rises of four readings, the first one wins
+        assert_eq!(report.longest_increase, Some(Run { start: 0, len: 4 }));
+        assert_eq!(report.plateaus, []);
+        assert_eq!(report.largest_drop, Some(DepthDrop { at: 4, amount: 10 }));
+        assert_eq!(
+            report.largest_window_drop,
+            Some(DepthDrop { at: 5, amount: 1 })
+        );
+
+        let report = Report::new([5, 5, 5, 3, 4, 4, 6, 7, 8, 8], 3);
+        assert_eq!(report.longest_increase, Some(Run { start: 5, len: 4 }));
+        assert_eq!(
+            report.plateaus,
+            [
+                Run { start: 0, len: 3 },
+                Run { start: 4, len: 2 },
+                Run { start: 8, len: 2 },
+            ]
+        );
+        assert_eq!(report.largest_drop, Some(DepthDrop { at: 3, amount: 2 }));
+        assert_eq!(
+            report.largest_window_drop,
+            Some(DepthDrop { at: 3, amount: 2 })
+        );
+
+        let report = Report::new([], 3);
+        assert_eq!(report.readings, 0);
+        assert_eq!(report.longest_increase, None);
+        assert_eq!(report.largest_drop, None);
+    }
+}
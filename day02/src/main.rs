@@ -1,34 +1,294 @@
-fn part1(data: &Vec<Vec<&str>>) -> i64 {
-    let (x, y) = data.iter().fold((0, 0), |(x, y), operation| {
-        let value: i64 = operation[1].parse().unwrap();
-        match operation[0] {
-            "forward" => (x + value, y),
-            "up" => (x, y - value),
-            "down" => (x, y + value),
-            _ => unreachable!(),
+use std::{fmt, str::FromStr};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Command {
+    Forward(i64),
+    Up(i64),
+    Down(i64),
+}
+
+#[derive(Debug, PartialEq)]
+enum ParseError {
+    UnknownCommand(String),
+    MissingValue(String),
+    InvalidValue(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnknownCommand(line) => write!(f, "unknown command in {:?}", line),
+            Self::MissingValue(line) => write!(f, "missing value in {:?}", line),
+            Self::InvalidValue(line) => write!(f, "invalid value in {:?}", line),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl FromStr for Command {
+    type Err = ParseError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut words = line.split_whitespace();
+        let command: fn(i64) -> Self = match words.next() {
+            Some("forward") => Self::Forward,
+            Some("up") => Self::Up,
+            Some("down") => Self::Down,
+            _ => return Err(ParseError::UnknownCommand(line.to_owned())),
+        };
+        let value = words
+            .next()
+            .ok_or_else(|| ParseError::MissingValue(line.to_owned()))?
+            .parse()
+            .map_err(|_| ParseError::InvalidValue(line.to_owned()))?;
+        if words.next().is_some() {
+            return Err(ParseError::InvalidValue(line.to_owned()));
+        }
+        Ok(command(value))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Position {
+    x: i64,
+    depth: i64,
+    aim: i64,
+}
+
+// How a submarine interprets its commands. New command sets only need a new
+// model, the submarine itself stays the same.
+trait Model {
+    type Command;
+
+    fn apply(&self, position: Position, command: &Self::Command) -> Position;
+}
+
+struct Direct;
+
+impl Model for Direct {
+    type Command = Command;
+
+    fn apply(&self, p: Position, command: &Command) -> Position {
+        match *command {
+            Command::Forward(v) => Position { x: p.x + v, ..p },
+            Command::Up(v) => Position {
+                depth: p.depth - v,
+                ..p
+            },
+            Command::Down(v) => Position {
+                depth: p.depth + v,
+                ..p
+            },
+        }
+    }
+}
+
+struct Aim;
+
+impl Model for Aim {
+    type Command = Command;
+
+    fn apply(&self, p: Position, command: &Command) -> Position {
+        match *command {
+            Command::Forward(v) => Position {
+                x: p.x + v,
+                depth: p.depth + v * p.aim,
+                ..p
+            },
+            Command::Up(v) => Position {
+                aim: p.aim - v,
+                ..p
+            },
+            Command::Down(v) => Position {
+                aim: p.aim + v,
+                ..p
+            },
         }
-    });
-    x * y
+    }
 }
 
-fn part2(data: &Vec<Vec<&str>>) -> i64 {
-    let (x, y, _) = data.iter().fold((0, 0, 0), |(x, y, aim), operation| {
-        let value: i64 = operation[1].parse().unwrap();
-        match operation[0] {
-            "forward" => (x + value, y + value * aim, aim),
-            "up" => (x, y, aim - value),
-            "down" => (x, y, aim + value),
-            _ => unreachable!(),
+// The submarine keeps every position it has been in, starting at the surface.
+struct Submarine<M: Model> {
+    model: M,
+    path: Vec<Position>,
+}
+
+impl<M: Model> Submarine<M> {
+    fn new(model: M) -> Self {
+        Self {
+            model,
+            path: vec![Position::default()],
+        }
+    }
+
+    fn position(&self) -> Position {
+        *self.path.last().unwrap()
+    }
+
+    fn execute(&mut self, command: &M::Command) {
+        let position = self.model.apply(self.position(), command);
+        self.path.push(position);
+    }
+
+    fn run<'a>(mut self, commands: impl IntoIterator<Item = &'a M::Command>) -> Self
+    where
+        M::Command: 'a,
+    {
+        for command in commands {
+            self.execute(command);
         }
-    });
-    x * y
+        self
+    }
+
+    // The step at which the submarine first reached its greatest depth.
+    fn max_depth(&self) -> (usize, Position) {
+        self.path
+            .iter()
+            .copied()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_, p)| p.depth)
+            .unwrap()
+    }
+
+    fn to_csv(&self) -> String {
+        let mut csv = "step,x,depth,aim\n".to_owned();
+        for (step, p) in self.path.iter().enumerate() {
+            csv += &format!("{},{},{},{}\n", step, p.x, p.depth, p.aim);
+        }
+        csv
+    }
+}
+
+// The first step after which two submarines are no longer in the same place.
+fn divergence<A: Model, B: Model>(a: &Submarine<A>, b: &Submarine<B>) -> Option<usize> {
+    a.path
+        .iter()
+        .zip(&b.path)
+        .position(|(a, b)| (a.x, a.depth) != (b.x, b.depth))
+}
+
+fn part1(data: &[Command]) -> i64 {
+    let Position { x, depth, .. } = Submarine::new(Direct).run(data).position();
+    x * depth
+}
+
+fn part2(data: &[Command]) -> i64 {
+    let Position { x, depth, .. } = Submarine::new(Aim).run(data).position();
+    x * depth
 }
 
 fn main() {
-    let data = include_str!("../input.txt")
+    let data: Vec<_> = include_str!("../input.txt")
         .lines()
-        .map(|line| line.split_whitespace().collect())
+        .map(|line| line.parse().unwrap())
         .collect();
+    let direct = Submarine::new(Direct).run(&data);
+    let aim = Submarine::new(Aim).run(&data);
+
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    // `cargo run -- --csv direct|aim` exports the whole course
+    if let Some(i) = args.iter().position(|arg| arg == "--csv") {
+        match args.get(i + 1).map(String::as_str) {
+            Some("direct") => print!("{}", direct.to_csv()),
+            Some("aim") => print!("{}", aim.to_csv()),
+            _ => eprintln!("Usage: --csv direct|aim"),
+        }
+        return;
+    }
+
     println!("Part 1: {}", part1(&data));
     println!("Part 2: {}", part2(&data));
+    if args.iter().any(|arg| arg == "--summary") {
+        for (name, (step, p)) in [("direct", direct.max_depth()), ("aim", aim.max_depth())] {
+            println!(
+                "Max depth ({}): {} at step {} (x = {})",
+                name, p.depth, step, p.x
+            );
+        }
+        match divergence(&direct, &aim) {
+            Some(step) => println!("Models diverge at step {}", step),
+            None => println!("Models never diverge"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const EXAMPLE: &str = "forward 5\ndown 5\nforward 8\nup 3\ndown 8\nforward 2";
+
+    fn example() -> Vec<Command> {
+        EXAMPLE.lines().map(|line| line.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for (s, e) in [
+            ("", ParseError::UnknownCommand("".to_owned())),
+            ("foo", ParseError::UnknownCommand("foo".to_owned())),
+            ("back 3", ParseError::UnknownCommand("back 3".to_owned())),
+            ("up", ParseError::MissingValue("up".to_owned())),
+            ("down x", ParseError::InvalidValue("down x".to_owned())),
+            (
+                "forward 1 2",
+                ParseError::InvalidValue("forward 1 2".to_owned()),
+            ),
+        ] {
+            assert_eq!(s.parse::<Command>(), Err(e));
+        }
+        assert_eq!(" up  7 ".parse(), Ok(Command::Up(7)));
+    }
+
+    #[test]
+    fn test_models() {
+        assert_eq!(part1(&example()), 150);
+        assert_eq!(part2(&example()), 900);
+        assert_eq!(
+            Submarine::new(Aim).run(&example()).position(),
+            Position {
+                x: 15,
+                depth: 60,
+                aim: 10
+            }
+        );
+    }
+
+    #[test]
+    fn test_course() {
+        let direct = Submarine::new(Direct).run(&example());
+        let aim = Submarine::new(Aim).run(&example());
+        // Depth 10 is reached at steps 5 and 6, the first one counts
+        let (step, p) = direct.max_depth();
+        assert_eq!((step, p.x, p.depth), (5, 13, 10));
+        let (step, p) = aim.max_depth();
+        assert_eq!((step, p.x, p.depth), (6, 15, 60));
+        // Aim only changes depth from the second forward on
+        let (step, _) = Submarine::new(Aim).run(&example()[..5]).max_depth();
+        assert_eq!(step, 3);
+
+        assert_eq!(
+            aim.to_csv(),
+            "step,x,depth,aim\n\
+             0,0,0,0\n\
+             1,5,0,0\n\
+             2,5,0,5\n\
+             3,13,40,5\n\
+             4,13,40,2\n\
+             5,13,40,10\n\
+             6,15,60,10\n"
+        );
+        assert_eq!(direct.to_csv().lines().nth(3), Some("2,5,5,0"));
+
+        assert_eq!(divergence(&direct, &aim), Some(2));
+        let forward = [Command::Forward(3), Command::Forward(4)];
+        let (a, b) = (
+            Submarine::new(Direct).run(&forward),
+            Submarine::new(Aim).run(&forward),
+        );
+        assert_eq!(divergence(&a, &b), None);
+    }
 }
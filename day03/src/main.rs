@@ -1,47 +1,397 @@
-fn digit_delta(data: &[&str], index: usize) -> i32 {
-    data.iter().fold(0, |delta, &line| {
-        delta
-            + match line.chars().nth(index).unwrap() {
-                '1' => 1,
-                '0' => -1,
-                _ => unreachable!(),
+use num_bigint::BigUint;
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, PartialEq)]
+enum ParseError {
+    InvalidDigit(char),
+    Empty,
+    Ragged { line: usize, width: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidDigit(ch) => write!(f, "invalid binary digit {:?}", ch),
+            Self::Empty => write!(f, "empty report"),
+            Self::Ragged { line, width } => {
+                write!(f, "line {} is not {} bits wide", line + 1, width)
             }
-    })
+        }
+    }
 }
 
-fn part1(data: &[&str]) -> u32 {
-    let (gamma, epsilon) = (0..data[0].len()).fold((0, 0), |(gamma, epsilon), index| {
-        let nth = if digit_delta(data, index) > 0 { 1 } else { 0 };
-        ((gamma << 1) | nth, (epsilon << 1) | (1 - nth))
-    });
-    gamma * epsilon
+impl std::error::Error for ParseError {}
+
+// Column `i` of a report line is bit `i % 64` of word `i / 64`, so column 0 is
+// the most significant digit of the line.
+#[derive(Clone, Debug, PartialEq)]
+struct Bits {
+    words: Vec<u64>,
+    width: usize,
 }
 
-fn life_support_rating(data: &[&str], default: char) -> u32 {
-    let mut data = data.to_owned();
-    let mut i = 0;
-    while data.len() > 1 {
-        let nth = if digit_delta(&data, i) >= 0 {
-            default
-        } else {
-            if default == '0' {
-                '1'
-            } else {
-                '0'
+impl Bits {
+    fn zeros(width: usize) -> Self {
+        Self {
+            words: vec![0; width.div_ceil(64)],
+            width,
+        }
+    }
+
+    fn get(&self, i: usize) -> bool {
+        self.words[i / 64] >> (i % 64) & 1 == 1
+    }
+
+    fn set(&mut self, i: usize) {
+        self.words[i / 64] |= 1 << (i % 64);
+    }
+
+    fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &word)| {
+            let mut word = word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(i * 64 + bit)
+            })
+        })
+    }
+
+    fn value(&self) -> BigUint {
+        let mut value = BigUint::ZERO;
+        for i in self.ones() {
+            value.set_bit((self.width - 1 - i) as u64, true);
+        }
+        value
+    }
+}
+
+impl FromStr for Bits {
+    type Err = ParseError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let line = line.trim();
+        let mut bits = Self::zeros(line.len());
+        for (i, ch) in line.chars().enumerate() {
+            match ch {
+                '1' => bits.set(i),
+                '0' => {}
+                _ => return Err(ParseError::InvalidDigit(ch)),
             }
+        }
+        Ok(bits)
+    }
+}
+
+impl fmt::Display for Bits {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for i in 0..self.width {
+            write!(f, "{}", if self.get(i) { '1' } else { '0' })?;
+        }
+        Ok(())
+    }
+}
+
+struct Report {
+    lines: Vec<Bits>,
+    width: usize,
+}
+
+impl FromStr for Report {
+    type Err = ParseError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let lines = input
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(str::parse)
+            .collect::<Result<Vec<Bits>, _>>()?;
+        let width = lines.first().ok_or(ParseError::Empty)?.width;
+        if let Some(line) = lines.iter().position(|line| line.width != width) {
+            return Err(ParseError::Ragged { line, width });
+        }
+        Ok(Self { lines, width })
+    }
+}
+
+// The number of lines with a 1 in each column, counted in a single pass.
+fn column_counts(lines: &[Bits], width: usize) -> Vec<usize> {
+    let mut counts = vec![0; width];
+    for line in lines {
+        for i in line.ones() {
+            counts[i] += 1;
+        }
+    }
+    counts
+}
+
+fn product(a: &Bits, b: &Bits) -> BigUint {
+    a.value() * b.value()
+}
+
+fn part1(report: &Report) -> BigUint {
+    let mut gamma = Bits::zeros(report.width);
+    let mut epsilon = Bits::zeros(report.width);
+    for (i, ones) in column_counts(&report.lines, report.width)
+        .into_iter()
+        .enumerate()
+    {
+        if ones * 2 > report.lines.len() {
+            gamma.set(i);
+        } else {
+            epsilon.set(i);
+        }
+    }
+    product(&gamma, &epsilon)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Keep {
+    MostCommon,
+    LeastCommon,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum BitOrder {
+    MsbFirst,
+    LsbFirst,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Criteria {
+    keep: Keep,
+    // The bit to keep when a column has as many ones as zeros
+    tie: bool,
+    order: BitOrder,
+}
+
+impl Criteria {
+    const OXYGEN: Self = Self {
+        keep: Keep::MostCommon,
+        tie: true,
+        order: BitOrder::MsbFirst,
+    };
+    const CO2: Self = Self {
+        keep: Keep::LeastCommon,
+        tie: false,
+        order: BitOrder::MsbFirst,
+    };
+
+    fn bit(&self, ones: usize, lines: usize) -> bool {
+        let zeros = lines - ones;
+        if ones == zeros {
+            return self.tie;
+        }
+        match self.keep {
+            Keep::MostCommon => ones > zeros,
+            Keep::LeastCommon => ones < zeros,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Step {
+    column: usize,
+    kept: bool,
+    remaining: usize,
+}
+
+// Filters the report column by column until one line is left. A column where
+// every line has the same bit keeps them all, even when the criteria asks for
+// the least common bit.
+fn life_support_rating(report: &Report, criteria: Criteria) -> (Bits, Vec<Step>) {
+    let mut lines: Vec<_> = report.lines.iter().collect();
+    let mut steps = vec![];
+    let columns: Box<dyn Iterator<Item = usize>> = match criteria.order {
+        BitOrder::MsbFirst => Box::new(0..report.width),
+        BitOrder::LsbFirst => Box::new((0..report.width).rev()),
+    };
+    for column in columns {
+        if lines.len() < 2 {
+            break;
+        }
+        let ones = lines.iter().filter(|line| line.get(column)).count();
+        let kept = match ones {
+            0 => false,
+            n if n == lines.len() => true,
+            n => criteria.bit(n, lines.len()),
         };
-        data.retain(|line| line.chars().nth(i).unwrap() == nth);
-        i += 1;
+        lines.retain(|line| line.get(column) == kept);
+        steps.push(Step {
+            column,
+            kept,
+            remaining: lines.len(),
+        });
     }
-    u32::from_str_radix(data[0], 2).unwrap()
+    (lines[0].clone(), steps)
 }
 
-fn part2(data: &[&str]) -> u32 {
-    life_support_rating(data, '1') * life_support_rating(data, '0')
+fn part2(report: &Report) -> BigUint {
+    product(
+        &life_support_rating(report, Criteria::OXYGEN).0,
+        &life_support_rating(report, Criteria::CO2).0,
+    )
 }
 
 fn main() {
-    let data: Vec<_> = include_str!("../input.txt").lines().collect();
-    println!("Part 1: {}", part1(&data));
-    println!("Part 2: {}", part2(&data));
+    let report: Report = include_str!("../input.txt").parse().unwrap();
+    println!("Part 1: {}", part1(&report));
+    println!("Part 2: {}", part2(&report));
+    // `cargo run -- --steps [--lsb-first]` shows how each rating was filtered
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--steps") {
+        let order = if args.iter().any(|arg| arg == "--lsb-first") {
+            BitOrder::LsbFirst
+        } else {
+            BitOrder::MsbFirst
+        };
+        for (name, criteria) in [("Oxygen", Criteria::OXYGEN), ("CO2", Criteria::CO2)] {
+            let (rating, steps) = life_support_rating(&report, Criteria { order, ..criteria });
+            println!("{} rating: {}", name, rating);
+            for Step {
+                column,
+                kept,
+                remaining,
+            } in steps
+            {
+                println!(
+                    "  column {:>3}: kept {}, {} remaining",
+                    column, kept as u8, remaining
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const EXAMPLE: &str = "00100\n11110\n10110\n10111\n10101\n01111\n\
+                           00111\n11100\n10000\n11001\n00010\n01010\n";
+
+    #[test]
+    fn test_example() {
+        let report: Report = EXAMPLE.parse().unwrap();
+        assert_eq!(part1(&report), BigUint::from(198u32));
+        assert_eq!(part2(&report), BigUint::from(230u32));
+    }
+
+    #[test]
+    fn test_wide_lines() {
+        // 65 bits: the top column spills into a second word
+        let one = format!("1{}", "0".repeat(64));
+        let report: Report = format!("{}\n{}\n{}\n", one, one, "0".repeat(65))
+            .parse()
+            .unwrap();
+        let top = BigUint::from(1u8) << 64;
+        assert_eq!(report.lines[0].value(), top);
+        // gamma has only the top bit set, epsilon every other bit
+        assert_eq!(part1(&report), &top * (&top - 1u8));
+
+        // 200 bits, far past u128
+        let width = 200;
+        let line = |bit: usize| {
+            let mut line = "0".repeat(width);
+            line.replace_range(bit..=bit, "1");
+            line
+        };
+        let report: Report = [line(0), line(0), line(199)].join("\n").parse().unwrap();
+        let top = BigUint::from(1u8) << (width - 1);
+        let all = (BigUint::from(1u8) << width) - 1u8;
+        assert_eq!(part1(&report), &top * (&all - &top));
+        assert_eq!(part2(&report), top);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for (s, e) in [
+            ("", ParseError::Empty),
+            ("\n  \n", ParseError::Empty),
+            ("0101\n0121\n", ParseError::InvalidDigit('2')),
+            ("0101\n011\n", ParseError::Ragged { line: 1, width: 4 }),
+        ] {
+            assert_eq!(s.parse::<Report>().err(), Some(e));
+        }
+    }
+
+    fn steps(report: &Report, criteria: Criteria) -> (String, Vec<(usize, bool, usize)>) {
+        let (rating, steps) = life_support_rating(report, criteria);
+        let steps = steps
+            .iter()
+            .map(|step| (step.column, step.kept, step.remaining))
+            .collect();
+        (rating.to_string(), steps)
+    }
+
+    #[test]
+    fn test_steps() {
+        let report: Report = EXAMPLE.parse().unwrap();
+        assert_eq!(
+            steps(&report, Criteria::OXYGEN),
+            (
+                "10111".to_owned(),
+                vec![
+                    (0, true, 7),
+                    (1, false, 4),
+                    (2, true, 3),
+                    (3, true, 2),
+                    (4, true, 1)
+                ]
+            )
+        );
+        assert_eq!(
+            steps(&report, Criteria::CO2),
+            (
+                "01010".to_owned(),
+                vec![(0, false, 5), (1, true, 2), (2, false, 1)]
+            )
+        );
+
+        let lsb_first = |criteria| Criteria {
+            order: BitOrder::LsbFirst,
+            ..criteria
+        };
+        assert_eq!(
+            steps(&report, lsb_first(Criteria::OXYGEN)),
+            (
+                "11110".to_owned(),
+                vec![(4, false, 7), (3, true, 4), (2, true, 2), (1, true, 1)]
+            )
+        );
+        assert_eq!(
+            steps(&report, lsb_first(Criteria::CO2)),
+            (
+                "11001".to_owned(),
+                vec![(4, true, 5), (3, false, 2), (2, false, 1)]
+            )
+        );
+    }
+
+    #[test]
+    fn test_ties() {
+        let report: Report = "10\n01\n11\n00\n".parse().unwrap();
+        let most_common = |tie| Criteria {
+            tie,
+            ..Criteria::OXYGEN
+        };
+        assert_eq!(
+            steps(&report, most_common(false)),
+            ("00".to_owned(), vec![(0, false, 2), (1, false, 1)])
+        );
+        assert_eq!(
+            steps(&report, most_common(true)),
+            ("11".to_owned(), vec![(0, true, 2), (1, true, 1)])
+        );
+        // A column where every line agrees keeps them all, even for CO2
+        let report: Report = "11\n10\n".parse().unwrap();
+        assert_eq!(
+            steps(&report, Criteria::CO2),
+            ("10".to_owned(), vec![(0, true, 2), (1, false, 1)])
+        );
+    }
 }
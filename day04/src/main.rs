@@ -1,8 +1,295 @@
-#![feature(drain_filter)]
+use std::collections::HashMap;
+use std::str::FromStr;
 
-type Board = Vec<Vec<i32>>;
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Pattern {
+    Rows,
+    Columns,
+    Diagonals,
+    Corners,
+    Full,
+}
+
+impl Pattern {
+    // The cell indices of every line this pattern wins with on a board of the
+    // given size. Diagonals only exist on square boards.
+    fn lines(&self, rows: usize, cols: usize) -> Vec<Vec<usize>> {
+        let cell = |i: usize, j: usize| i * cols + j;
+        match self {
+            Self::Rows => (0..rows)
+                .map(|i| (0..cols).map(|j| cell(i, j)).collect())
+                .collect(),
+            Self::Columns => (0..cols)
+                .map(|j| (0..rows).map(|i| cell(i, j)).collect())
+                .collect(),
+            Self::Diagonals if rows == cols => vec![
+                (0..rows).map(|i| cell(i, i)).collect(),
+                (0..rows).map(|i| cell(i, cols - 1 - i)).collect(),
+            ],
+            Self::Diagonals => vec![],
+            Self::Corners => {
+                let mut corners = vec![
+                    cell(0, 0),
+                    cell(0, cols - 1),
+                    cell(rows - 1, 0),
+                    cell(rows - 1, cols - 1),
+                ];
+                corners.sort_unstable();
+                corners.dedup();
+                vec![corners]
+            }
+            Self::Full => vec![(0..rows * cols).collect()],
+        }
+    }
+
+    fn label(&self, k: usize) -> String {
+        match (self, k) {
+            (Self::Rows, _) => format!("row {}", k),
+            (Self::Columns, _) => format!("column {}", k),
+            (Self::Diagonals, 0) => "diagonal".to_owned(),
+            (Self::Diagonals, _) => "anti-diagonal".to_owned(),
+            (Self::Corners, _) => "corners".to_owned(),
+            (Self::Full, _) => "full card".to_owned(),
+        }
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rows" => Ok(Self::Rows),
+            "columns" => Ok(Self::Columns),
+            "diagonals" => Ok(Self::Diagonals),
+            "corners" => Ok(Self::Corners),
+            "full" => Ok(Self::Full),
+            _ => Err(format!("unknown pattern {:?}", s)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Board {
+    rows: usize,
+    cols: usize,
+    numbers: Vec<u32>,
+    marked: Vec<bool>,
+    unmarked: u32,
+}
+
+impl Board {
+    fn new(rows: Vec<Vec<u32>>) -> Self {
+        let cols = rows.first().map_or(0, Vec::len);
+        assert!(
+            cols > 0 && rows.iter().all(|row| row.len() == cols),
+            "boards must be non-empty rectangles"
+        );
+        Self {
+            rows: rows.len(),
+            cols,
+            marked: vec![false; rows.len() * cols],
+            unmarked: rows.iter().flatten().sum(),
+            numbers: rows.into_iter().flatten().collect(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Win {
+    board: usize,
+    turn: usize,
+    value: u32,
+    score: u32,
+    line: usize,
+}
+
+// The winning lines of one board size, and the lines through each cell
+struct Layout {
+    lines: Vec<Vec<usize>>,
+    labels: Vec<String>,
+    lines_through: Vec<Vec<usize>>,
+}
+
+impl Layout {
+    fn new(rows: usize, cols: usize, patterns: &[Pattern]) -> Self {
+        let (lines, labels): (Vec<_>, Vec<_>) = patterns
+            .iter()
+            .flat_map(|pattern| {
+                let lines = pattern.lines(rows, cols);
+                lines
+                    .into_iter()
+                    .enumerate()
+                    .map(|(k, line)| (line, pattern.label(k)))
+            })
+            .unzip();
+        let mut lines_through = vec![vec![]; rows * cols];
+        for (l, line) in lines.iter().enumerate() {
+            for &cell in line {
+                lines_through[cell].push(l);
+            }
+        }
+        Self {
+            lines,
+            labels,
+            lines_through,
+        }
+    }
+}
+
+// Every number points at the cells holding it, and every line counts its
+// marked cells, so a draw only touches the boards that contain it. Boards of
+// the same size share one layout.
+struct Bingo {
+    boards: Vec<Board>,
+    index: HashMap<u32, Vec<(usize, usize, usize)>>,
+    layouts: Vec<Layout>,
+    layout: Vec<usize>,
+    // Per board, the number of marked cells in each line
+    hits: Vec<Vec<usize>>,
+    won: Vec<bool>,
+}
+
+impl Bingo {
+    fn new(boards: Vec<Board>, patterns: &[Pattern]) -> Self {
+        let mut index: HashMap<_, Vec<_>> = HashMap::new();
+        let mut shapes = HashMap::new();
+        let mut layouts = vec![];
+        let mut layout = vec![];
+        for (b, board) in boards.iter().enumerate() {
+            for (cell, &number) in board.numbers.iter().enumerate() {
+                index
+                    .entry(number)
+                    .or_default()
+                    .push((b, cell / board.cols, cell % board.cols));
+            }
+            layout.push(*shapes.entry((board.rows, board.cols)).or_insert_with(|| {
+                layouts.push(Layout::new(board.rows, board.cols, patterns));
+                layouts.len() - 1
+            }));
+        }
+        Self {
+            won: vec![false; boards.len()],
+            hits: layout
+                .iter()
+                .map(|&l| vec![0; layouts[l].lines.len()])
+                .collect(),
+            boards,
+            index,
+            layouts,
+            layout,
+        }
+    }
+
+    fn label(&self, win: &Win) -> &str {
+        &self.layouts[self.layout[win.board]].labels[win.line]
+    }
+
+    // Marks `value` on every board still in play, returning the boards that
+    // won with it. A board stops playing once it has won.
+    fn call(&mut self, turn: usize, value: u32) -> Vec<Win> {
+        let mut winners: Vec<(usize, usize)> = vec![];
+        for &(b, i, j) in self.index.get(&value).map_or(&[][..], Vec::as_slice) {
+            let board = &mut self.boards[b];
+            let cell = i * board.cols + j;
+            if self.won[b] || board.marked[cell] {
+                continue;
+            }
+            board.marked[cell] = true;
+            board.unmarked -= value;
+            let layout = &self.layouts[self.layout[b]];
+            for &l in &layout.lines_through[cell] {
+                self.hits[b][l] += 1;
+                if self.hits[b][l] == layout.lines[l].len()
+                    && winners.last().map(|&(w, _)| w) != Some(b)
+                {
+                    winners.push((b, l));
+                }
+            }
+        }
+        // Scores wait until every copy of `value` on a winning board is marked
+        winners
+            .into_iter()
+            .map(|(b, line)| {
+                self.won[b] = true;
+                Win {
+                    board: b,
+                    turn,
+                    value,
+                    score: value * self.boards[b].unmarked,
+                    line,
+                }
+            })
+            .collect()
+    }
+
+    fn reset(&mut self) {
+        for board in &mut self.boards {
+            board.marked.fill(false);
+            board.unmarked = board.numbers.iter().sum();
+        }
+        self.hits.iter_mut().for_each(|hits| hits.fill(0));
+        self.won.fill(false);
+    }
+
+    fn play(&mut self, values: &[u32]) -> Vec<Win> {
+        values
+            .iter()
+            .enumerate()
+            .flat_map(|(turn, &value)| self.call(turn, value))
+            .collect()
+    }
+}
+
+// SplitMix64, so simulations are reproducible from their seed
+struct Rng(u64);
 
-fn read_boards(input: &str) -> (Vec<i32>, Vec<Board>) {
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, (self.next() % (i as u64 + 1)) as usize);
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct BoardOdds {
+    firsts: usize,
+    wins: usize,
+    turns: usize,
+}
+
+// Plays `runs` games with the draws shuffled, counting for each board how often
+// it won first (boards tied on the first winning draw all count) and the turns
+// on which it won.
+fn simulate(bingo: &mut Bingo, values: &[u32], runs: usize, seed: u64) -> Vec<BoardOdds> {
+    let mut rng = Rng(seed);
+    let mut values = values.to_owned();
+    let mut odds = vec![BoardOdds::default(); bingo.boards.len()];
+    for _ in 0..runs {
+        rng.shuffle(&mut values);
+        bingo.reset();
+        let wins = bingo.play(&values);
+        for win in &wins {
+            let board = &mut odds[win.board];
+            board.firsts += (win.turn == wins[0].turn) as usize;
+            board.wins += 1;
+            board.turns += win.turn + 1;
+        }
+    }
+    bingo.reset();
+    odds
+}
+
+fn read_boards(input: &str) -> (Vec<u32>, Vec<Board>) {
     let parts = input.split("\n\n").collect::<Vec<_>>();
 
     let values = parts[0]
@@ -13,70 +300,233 @@ fn read_boards(input: &str) -> (Vec<i32>, Vec<Board>) {
     let boards = parts[1..]
         .iter()
         .map(|board_part| {
-            board_part
-                .lines()
-                .map(|board_row| {
-                    board_row
-                        .split_whitespace()
-                        .map(|board_cell| board_cell.parse().unwrap())
-                        .collect()
-                })
-                .collect()
+            Board::new(
+                board_part
+                    .lines()
+                    .filter(|board_row| !board_row.trim().is_empty())
+                    .map(|board_row| {
+                        board_row
+                            .split_whitespace()
+                            .map(|board_cell| board_cell.parse().unwrap())
+                            .collect()
+                    })
+                    .collect(),
+            )
         })
         .collect();
-    return (values, boards);
+    (values, boards)
 }
 
-fn score_boards(values: &[i32], mut boards: Vec<Board>) -> Vec<i32> {
-    let mut success = Vec::new();
-    for &value in values {
-        boards.drain_filter(|board| {
-            if let Some(score) = call_num(value, board) {
-                success.push(score);
-                true
-            } else {
-                false
-            }
-        });
-    }
-    return success;
+fn part1(wins: &[Win]) -> u32 {
+    wins.first().unwrap().score
 }
 
-fn sum_unmarked(board: &Board) -> i32 {
-    board.iter().flatten().filter(|&&x| x > 0).sum()
+fn part2(wins: &[Win]) -> u32 {
+    wins.last().unwrap().score
 }
 
-fn completed(board: &Board, i: usize, j: usize) -> bool {
-    board[i].iter().all(|&x| x < 0) || board.iter().map(|row| row[j]).all(|x| x < 0)
-}
+fn main() {
+    // `cargo run -- --patterns rows,columns,diagonals,corners,full`
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    let patterns: Vec<Pattern> = match args.iter().position(|arg| arg == "--patterns") {
+        Some(i) => args[i + 1].split(',').map(|p| p.parse().unwrap()).collect(),
+        None => vec![Pattern::Rows, Pattern::Columns],
+    };
+    let (values, boards) = read_boards(include_str!("../input.txt"));
+    let mut bingo = Bingo::new(boards, &patterns);
+    let wins = bingo.play(&values);
+    println!("Part 1: {}", part1(&wins));
+    println!("Part 2: {}", part2(&wins));
 
-fn call_num(value: i32, board: &mut Board) -> Option<i32> {
-    for i in 0..5 {
-        for j in 0..5 {
-            if board[i][j] == value {
-                board[i][j] = -1;
-                return if completed(board, i, j) {
-                    Some(value * sum_unmarked(board))
-                } else {
-                    None
-                };
-            }
+    // `cargo run -- --timeline` lists every win in order
+    if args.iter().any(|arg| arg == "--timeline") {
+        for win in &wins {
+            println!(
+                "Draw {:>3} ({:>2}): board {:>3} wins with {}, score {}",
+                win.turn + 1,
+                win.value,
+                win.board,
+                bingo.label(win),
+                win.score
+            );
         }
     }
-    None
-}
 
-fn part1(scores: &[i32]) -> i32 {
-    return *scores.first().unwrap();
+    // `cargo run -- --simulate <runs> [--seed <seed>]` shuffles the draws
+    if let Some(i) = args.iter().position(|arg| arg == "--simulate") {
+        let runs = args[i + 1].parse().unwrap();
+        let seed = match args.iter().position(|arg| arg == "--seed") {
+            Some(i) => args[i + 1].parse().unwrap(),
+            None => 0,
+        };
+        let odds = simulate(&mut bingo, &values, runs, seed);
+        println!("Board  P(first)  E[winning turn]");
+        for (b, board) in odds.iter().enumerate() {
+            let turn = if board.wins == 0 {
+                "never".to_owned()
+            } else {
+                format!("{:.2}", board.turns as f64 / board.wins as f64)
+            };
+            println!(
+                "{:>5}  {:>8.4}  {:>15}",
+                b,
+                board.firsts as f64 / runs as f64,
+                turn
+            );
+        }
+    }
 }
 
-fn part2(scores: &[i32]) -> i32 {
-    return *scores.last().unwrap();
-}
+#[cfg(test)]
+mod tests {
 
-fn main() {
-    let (values, boards) = read_boards(include_str!("../input.txt"));
-    let scores = score_boards(&values, boards);
-    println!("Part 1: {}", part1(&scores));
-    println!("Part 2: {}", part2(&scores));
+    use super::*;
+
+    fn winners(rows: Vec<Vec<u32>>, patterns: &[Pattern], values: &[u32]) -> Vec<(Win, String)> {
+        let mut bingo = Bingo::new(vec![Board::new(rows)], patterns);
+        let wins = bingo.play(values);
+        wins.into_iter()
+            .map(|win| (win, bingo.label(&win).to_owned()))
+            .collect()
+    }
+
+    fn square() -> Vec<Vec<u32>> {
+        vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]]
+    }
+
+    #[test]
+    fn test_patterns() {
+        let win = |patterns: &[Pattern], values: &[u32]| {
+            let wins = winners(square(), patterns, values);
+            assert_eq!(wins.len(), 1);
+            let (win, label) = &wins[0];
+            (win.turn, win.score, label.clone())
+        };
+        assert_eq!(
+            win(&[Pattern::Diagonals], &[3, 1, 5, 7]),
+            (3, 7 * 29, "anti-diagonal".to_owned())
+        );
+        assert_eq!(
+            win(&[Pattern::Diagonals], &[9, 5, 1]),
+            (2, 30, "diagonal".to_owned())
+        );
+        assert_eq!(
+            win(&[Pattern::Corners], &[1, 5, 3, 7, 9]),
+            (4, 9 * 20, "corners".to_owned())
+        );
+        assert_eq!(
+            win(&[Pattern::Full], &[9, 8, 7, 6, 5, 4, 3, 2, 1]),
+            (8, 0, "full card".to_owned())
+        );
+        assert_eq!(
+            win(&[Pattern::Rows, Pattern::Columns], &[4, 6, 5]),
+            (2, 5 * 30, "row 1".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_non_square_board() {
+        let board = || vec![vec![1, 2, 3], vec![4, 5, 6]];
+        let wins = winners(board(), &[Pattern::Rows, Pattern::Columns], &[2, 1, 4]);
+        assert_eq!(wins[0].0.turn, 2);
+        assert_eq!(wins[0].0.score, 4 * 14);
+        assert_eq!(wins[0].1, "column 0");
+        // Diagonals only exist on square boards
+        assert!(winners(board(), &[Pattern::Diagonals], &[1, 2, 3, 4, 5, 6]).is_empty());
+        let wins = winners(board(), &[Pattern::Corners], &[1, 3, 4, 6]);
+        assert_eq!(wins[0].0.turn, 3);
+    }
+
+    #[test]
+    fn test_zero_is_a_number() {
+        let board = || vec![vec![0, 1], vec![2, 3]];
+        // An unmarked 0 must not count as marked
+        assert!(winners(board(), &[Pattern::Rows], &[1]).is_empty());
+        let wins = winners(board(), &[Pattern::Rows], &[1, 0]);
+        assert_eq!((wins[0].0.turn, wins[0].0.score), (1, 0));
+        let wins = winners(board(), &[Pattern::Rows], &[0, 1]);
+        assert_eq!((wins[0].0.turn, wins[0].0.score), (1, 5));
+    }
+
+    #[test]
+    fn test_duplicate_numbers() {
+        // One draw marks both 7s and completes several lines, but the board
+        // wins once and its score counts neither 7
+        let patterns = [Pattern::Rows, Pattern::Columns, Pattern::Diagonals];
+        let wins = winners(vec![vec![7, 1], vec![1, 7]], &patterns, &[7, 1]);
+        assert_eq!(wins.len(), 1);
+        assert_eq!((wins[0].0.turn, wins[0].0.score), (0, 7 * 2));
+        assert_eq!(wins[0].1, "diagonal");
+        let wins = winners(vec![vec![7, 7], vec![1, 2]], &patterns, &[7]);
+        assert_eq!((wins[0].0.turn, wins[0].0.score), (0, 7 * 3));
+        assert_eq!(wins[0].1, "row 0");
+    }
+
+    #[test]
+    fn test_boards_stop_after_winning() {
+        let boards = vec![Board::new(square()), Board::new(square())];
+        let mut bingo = Bingo::new(boards, &[Pattern::Rows]);
+        let wins = bingo.play(&[1, 2, 3, 4, 5, 6]);
+        let boards: Vec<_> = wins.iter().map(|win| (win.board, win.turn)).collect();
+        assert_eq!(boards, [(0, 2), (1, 2)]);
+    }
+
+    #[test]
+    fn test_layouts_are_shared() {
+        let boards = vec![
+            Board::new(square()),
+            Board::new(vec![vec![1, 2, 3], vec![4, 5, 6]]),
+            Board::new(square()),
+        ];
+        let bingo = Bingo::new(boards, &[Pattern::Rows, Pattern::Columns]);
+        assert_eq!(bingo.layouts.len(), 2);
+        assert_eq!(bingo.layout, [0, 1, 0]);
+        assert_eq!(bingo.hits[1].len(), 5);
+    }
+
+    fn twins_and_other() -> Bingo {
+        let boards = vec![
+            Board::new(vec![vec![1, 2], vec![3, 4]]),
+            Board::new(vec![vec![5, 6], vec![7, 8]]),
+            Board::new(vec![vec![1, 2], vec![3, 4]]),
+        ];
+        Bingo::new(boards, &[Pattern::Rows, Pattern::Columns])
+    }
+
+    #[test]
+    fn test_timeline() {
+        let mut bingo = twins_and_other();
+        let wins = bingo.play(&[1, 5, 3, 7, 8, 2]);
+        let timeline: Vec<_> = wins
+            .iter()
+            .map(|win| (win.turn, win.board, bingo.label(win), win.score))
+            .collect();
+        assert_eq!(
+            timeline,
+            [
+                (2, 0, "column 0", 3 * 6),
+                (2, 2, "column 0", 3 * 6),
+                (3, 1, "column 0", 7 * 14),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_simulate() {
+        let values: Vec<_> = (1..=8).collect();
+        let mut bingo = twins_and_other();
+        let odds = simulate(&mut bingo, &values, 500, 7);
+        assert_eq!(odds, simulate(&mut twins_and_other(), &values, 500, 7));
+        assert_ne!(odds, simulate(&mut twins_and_other(), &values, 500, 8));
+
+        // The twins always tie, so both or neither get credit for each run
+        assert_eq!(odds[0], odds[2]);
+        assert_eq!(odds[0].firsts + odds[1].firsts, 500);
+        assert!(odds[0].firsts > 0 && odds[1].firsts > 0);
+        assert!(odds.iter().all(|board| board.wins == 500));
+        assert!(odds.iter().all(|board| board.turns >= 2 * 500));
+
+        // Simulating leaves the engine ready for a normal game
+        assert_eq!(bingo.play(&values), twins_and_other().play(&values));
+    }
 }
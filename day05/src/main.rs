@@ -1,59 +1,641 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 
-type Pair = (i32, i32, i32, i32);
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Line {
+    x0: i32,
+    y0: i32,
+    x1: i32,
+    y1: i32,
+}
 
-fn dir(a0: i32, a1: i32) -> i32 {
-    if a0 > a1 {
-        -1
-    } else if a0 < a1 {
-        1
-    } else {
-        0
+impl Line {
+    fn is_axis_aligned(&self) -> bool {
+        self.x0 == self.x1 || self.y0 == self.y1
+    }
+
+    fn is_diagonal(&self) -> bool {
+        (self.x0 - self.x1).abs() == (self.y0 - self.y1).abs()
+    }
+
+    // Always rasterised from the smaller end, so a line and its reverse cover
+    // the same points.
+    fn points(&self) -> Raster {
+        let ((x0, y0), (x1, y1)) = if (self.x0, self.y0) <= (self.x1, self.y1) {
+            ((self.x0, self.y0), (self.x1, self.y1))
+        } else {
+            ((self.x1, self.y1), (self.x0, self.y0))
+        };
+        let dx = (x1 - x0).abs();
+        let dy = -(y1 - y0).abs();
+        Raster {
+            x: x0,
+            y: y0,
+            end: (x1, y1),
+            dx,
+            dy,
+            sx: (x1 - x0).signum(),
+            sy: (y1 - y0).signum(),
+            err: dx + dy,
+            done: false,
+        }
+    }
+}
+
+// Integer Bresenham over every octant. Axis-aligned and 45° lines come out as
+// exactly the points on them.
+struct Raster {
+    x: i32,
+    y: i32,
+    end: (i32, i32),
+    dx: i32,
+    dy: i32,
+    sx: i32,
+    sy: i32,
+    err: i32,
+    done: bool,
+}
+
+impl Iterator for Raster {
+    type Item = (i32, i32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let point = (self.x, self.y);
+        if point == self.end {
+            self.done = true;
+            return Some(point);
+        }
+        let e2 = 2 * self.err;
+        if e2 >= self.dy {
+            self.err += self.dy;
+            self.x += self.sx;
+        }
+        if e2 <= self.dx {
+            self.err += self.dx;
+            self.y += self.sy;
+        }
+        Some(point)
     }
 }
 
-fn dist(pair: Pair) -> i32 {
-    let (x0, y0, x1, y1) = pair;
-    (x0 - x1).abs().max((y0 - y1).abs())
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Mode {
+    AxisAligned,
+    Diagonal,
+    AnyAngle,
+}
+
+impl Mode {
+    fn allows(&self, line: &Line) -> bool {
+        match self {
+            Self::AxisAligned => line.is_axis_aligned(),
+            Self::Diagonal => line.is_axis_aligned() || line.is_diagonal(),
+            Self::AnyAngle => true,
+        }
+    }
 }
 
-fn overlaps(pairs: &[Pair]) -> usize {
+fn density(lines: &[Line], mode: Mode) -> HashMap<(i32, i32), u32> {
     let mut map = HashMap::new();
-    for pair in pairs {
-        let &(x0, y0, x1, y1) = pair;
-        let dx = dir(x0, x1);
-        let dy = dir(y0, y1);
-        for i in 0..=dist(*pair) {
-            *map.entry((x0 + dx * i, y0 + dy * i)).or_insert(0) += 1;
+    for line in lines.iter().filter(|line| mode.allows(line)) {
+        for point in line.points() {
+            *map.entry(point).or_insert(0) += 1;
+        }
+    }
+    map
+}
+
+fn overlaps(lines: &[Line], mode: Mode) -> usize {
+    density(lines, mode)
+        .values()
+        .filter(|&&val| val > 1)
+        .count()
+}
+
+// The smallest box holding every vent, if there are any.
+fn bounds(map: &HashMap<(i32, i32), u32>) -> Option<(i32, i32, i32, i32)> {
+    let mut points = map.keys();
+    let &(x, y) = points.next()?;
+    Some(points.fold((x, y, x, y), |(x0, y0, x1, y1), &(x, y)| {
+        (x0.min(x), y0.min(y), x1.max(x), y1.max(y))
+    }))
+}
+
+// The puzzle draws its diagram from the origin.
+fn diagram_bounds(map: &HashMap<(i32, i32), u32>) -> (i32, i32, i32, i32) {
+    let (x0, y0, x1, y1) = bounds(map).unwrap_or((0, 0, 0, 0));
+    (x0.min(0), y0.min(0), x1.max(0), y1.max(0))
+}
+
+// The puzzle's diagram: `.` where there are no vents, the count elsewhere.
+fn diagram(map: &HashMap<(i32, i32), u32>) -> String {
+    let (x0, y0, x1, y1) = diagram_bounds(map);
+    let mut diagram = String::new();
+    for y in y0..=y1 {
+        for x in x0..=x1 {
+            diagram += &match map.get(&(x, y)) {
+                Some(count) => count.to_string(),
+                None => ".".to_owned(),
+            };
+        }
+        diagram += "\n";
+    }
+    diagram
+}
+
+// A plain PGM heatmap of the vents' bounding box, at most `max_size` pixels a
+// side. Each pixel shows the highest count among the cells it covers, scaled
+// so the highest count overall is white. Without vents it is a single black
+// pixel.
+fn heatmap(map: &HashMap<(i32, i32), u32>, max_size: usize) -> String {
+    let Some((x0, y0, x1, y1)) = bounds(map) else {
+        return "P2\n1 1\n255\n0\n".to_owned();
+    };
+    let (width, height) = ((x1 - x0) as usize + 1, (y1 - y0) as usize + 1);
+    let scale = width.max(height).div_ceil(max_size);
+    let (w, h) = (width.div_ceil(scale), height.div_ceil(scale));
+    let mut pixels = vec![0; w * h];
+    for (&(x, y), &count) in map {
+        let pixel = &mut pixels[(y - y0) as usize / scale * w + (x - x0) as usize / scale];
+        *pixel = count.max(*pixel);
+    }
+    let max = pixels.iter().copied().max().unwrap_or(0).max(1);
+    let mut pgm = format!("P2\n{} {}\n255\n", w, h);
+    for row in pixels.chunks(w) {
+        let row: Vec<_> = row.iter().map(|p| (p * 255 / max).to_string()).collect();
+        pgm += &row.join(" ");
+        pgm += "\n";
+    }
+    pgm
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum Direction {
+    Horizontal,
+    Vertical,
+    Rising,
+    Falling,
+}
+
+use Direction::*;
+
+// A line through the grid is a direction and a key: y for horizontal lines,
+// x for vertical ones, y - x for rising and y + x for falling diagonals. Points
+// on it are then identified by one coordinate `t`: y on vertical lines, x
+// everywhere else.
+type Key = (Direction, i64);
+type Span = (i64, i64);
+
+fn key_and_span(line: &Line) -> Option<(Key, Span)> {
+    let (x0, y0, x1, y1) = (
+        line.x0 as i64,
+        line.y0 as i64,
+        line.x1 as i64,
+        line.y1 as i64,
+    );
+    let key = if y0 == y1 {
+        (Horizontal, y0)
+    } else if x0 == x1 {
+        return Some(((Vertical, x0), (y0.min(y1), y0.max(y1))));
+    } else if x1 - x0 == y1 - y0 {
+        (Rising, y0 - x0)
+    } else if x1 - x0 == y0 - y1 {
+        (Falling, y0 + x0)
+    } else {
+        return None;
+    };
+    Some((key, (x0.min(x1), x0.max(x1))))
+}
+
+fn t((direction, _): Key, (x, y): (i64, i64)) -> i64 {
+    if direction == Vertical {
+        y
+    } else {
+        x
+    }
+}
+
+fn point((direction, key): Key, t: i64) -> (i64, i64) {
+    match direction {
+        Horizontal => (t, key),
+        Vertical => (key, t),
+        Rising => (t, t + key),
+        Falling => (t, key - t),
+    }
+}
+
+// The key of the line in `direction` through a point.
+fn key_at(direction: Direction, (x, y): (i64, i64)) -> i64 {
+    match direction {
+        Horizontal => y,
+        Vertical => x,
+        Rising => y - x,
+        Falling => y + x,
+    }
+}
+
+// The keys of the lines in `direction` that a span of the line `key` meets.
+fn key_range(key: Key, (a, b): Span, direction: Direction) -> Span {
+    let (ka, kb) = (
+        key_at(direction, point(key, a)),
+        key_at(direction, point(key, b)),
+    );
+    (ka.min(kb), ka.max(kb))
+}
+
+// The lattice point where two lines of different directions cross, if any.
+fn crossing(a: Key, b: Key) -> Option<Span> {
+    let (a, b) = if a.0 < b.0 { (a, b) } else { (b, a) };
+    match (a, b) {
+        ((Horizontal, y), (Vertical, x)) => Some((x, y)),
+        ((Horizontal, y), (Rising, k)) => Some((y - k, y)),
+        ((Horizontal, y), (Falling, k)) => Some((k - y, y)),
+        ((Vertical, x), (Rising, k)) => Some((x, x + k)),
+        ((Vertical, x), (Falling, k)) => Some((x, k - x)),
+        ((Rising, r), (Falling, f)) if (f - r) % 2 == 0 => Some(((f - r) / 2, (f + r) / 2)),
+        _ => None,
+    }
+}
+
+// Finds every crossing between spans of two directions with a sweep. Using the
+// keys of both directions as coordinates, spans in `a` run along the `b` axis
+// and spans in `b` along the `a` axis, so this is the classic sweep over
+// perpendicular segments: spans in `a` enter and leave an ordered set as the
+// sweep passes their `b` keys, and each span in `b` looks up the range of `a`
+// keys it covers. Rising and falling lines only cross on the lattice when
+// their keys have the same parity, so those are looked up in separate classes.
+// Takes O((n + k) log n) for n spans and k crossings.
+fn sweep_crossings(a: &[(Key, &[Span])], b: &[(Key, &[Span])], crossings: &mut HashSet<Span>) {
+    let (Some(&((da, _), _)), Some(&((db, _), _))) = (a.first(), b.first()) else {
+        return;
+    };
+    let class = |key: i64| match (da, db) {
+        (Rising, Falling) | (Falling, Rising) => key.rem_euclid(2),
+        _ => 0,
+    };
+    // Entries sort before lookups and lookups before exits at the same key
+    let mut events = vec![];
+    for &(key, spans) in a {
+        for &span in spans {
+            let (enter, exit) = key_range(key, span, db);
+            events.push((enter, 0, key.1, key.1));
+            events.push((exit, 2, key.1, key.1));
+        }
+    }
+    for &(key, spans) in b {
+        for &span in spans {
+            let (lo, hi) = key_range(key, span, da);
+            events.push((key.1, 1, lo, hi));
+        }
+    }
+    events.sort_unstable();
+
+    let mut active = BTreeSet::new();
+    for (key, kind, lo, hi) in events {
+        match kind {
+            0 => {
+                active.insert((class(lo), lo));
+            }
+            1 => {
+                let c = class(key);
+                for &(_, k) in active.range((c, lo)..=(c, hi)) {
+                    crossings.extend(crossing((da, k), (db, key)));
+                }
+            }
+            _ => {
+                active.remove(&(class(lo), lo));
+            }
         }
     }
-    map.values().filter(|&&val| val > 1).count()
 }
 
-fn part1(pairs: &[Pair]) -> usize {
-    let straight_lines: Vec<_> = pairs
+// Sweeps along one line, returning the merged spans covered at least once
+// and the merged spans covered at least twice.
+fn coverage(spans: &[Span]) -> (Vec<Span>, Vec<Span>) {
+    let mut events: Vec<_> = spans
+        .iter()
+        .flat_map(|&(a, b)| [(a, 1), (b + 1, -1)])
+        .collect();
+    events.sort_unstable();
+    let (mut once, mut twice) = (vec![], vec![]);
+    let mut depth = 0;
+    let mut i = 0;
+    while i < events.len() {
+        let t = events[i].0;
+        let before = depth;
+        while i < events.len() && events[i].0 == t {
+            depth += events[i].1;
+            i += 1;
+        }
+        for (spans, level) in [(&mut once, 1), (&mut twice, 2)] {
+            if before < level && depth >= level {
+                spans.push((t, t));
+            } else if before >= level && depth < level {
+                spans.last_mut().unwrap().1 = t - 1;
+            }
+        }
+    }
+    (once, twice)
+}
+
+fn covers(spans: &[Span], t: i64) -> bool {
+    let i = spans.partition_point(|&(_, b)| b < t);
+    spans.get(i).is_some_and(|&(a, _)| a <= t)
+}
+
+// Counts the points covered by at least two lines without visiting the points
+// along them. Collinear overlaps come from a sweep along each line, the rest
+// are crossings between lines of different directions, found by a sweep for
+// each pair of directions. Returns `None` if a line allowed by `mode` is
+// neither axis-aligned nor 45°.
+fn sweep_overlaps(lines: &[Line], mode: Mode) -> Option<usize> {
+    let mut groups: HashMap<Key, Vec<Span>> = HashMap::new();
+    for line in lines.iter().filter(|line| mode.allows(line)) {
+        let (key, span) = key_and_span(line)?;
+        groups.entry(key).or_default().push(span);
+    }
+    let groups: Vec<(Key, Vec<_>, Vec<_>)> = groups
+        .into_iter()
+        .map(|(key, spans)| {
+            let (once, twice) = coverage(&spans);
+            (key, once, twice)
+        })
+        .collect();
+    let twice: HashMap<Key, &[Span]> = groups
         .iter()
-        .filter(|(x0, y0, x1, y1)| x0 == x1 || y0 == y1)
-        .cloned()
+        .map(|(key, _, twice)| (*key, &twice[..]))
         .collect();
-    overlaps(&straight_lines)
+
+    let mut by_direction: HashMap<Direction, Vec<(Key, &[Span])>> = HashMap::new();
+    for (key, once, _) in &groups {
+        by_direction.entry(key.0).or_default().push((*key, once));
+    }
+    let directions = [Horizontal, Vertical, Rising, Falling];
+    let mut crossings = HashSet::new();
+    for (i, a) in directions.iter().enumerate() {
+        for b in &directions[i + 1..] {
+            if let (Some(a), Some(b)) = (by_direction.get(a), by_direction.get(b)) {
+                sweep_crossings(a, b, &mut crossings);
+            }
+        }
+    }
+
+    let collinear: i64 = twice
+        .values()
+        .flat_map(|spans| spans.iter())
+        .map(|(a, b)| b - a + 1)
+        .sum();
+    // A crossing is either new, or already counted once per direction it is
+    // doubled on, which overcounts it when that is more than one direction.
+    let adjustment: i64 = crossings
+        .iter()
+        .map(|&(x, y)| {
+            let doubled = [
+                (Horizontal, y),
+                (Vertical, x),
+                (Rising, y - x),
+                (Falling, y + x),
+            ]
+            .iter()
+            .filter(|key| {
+                twice
+                    .get(key)
+                    .is_some_and(|spans| covers(spans, t(**key, (x, y))))
+            })
+            .count() as i64;
+            if doubled == 0 {
+                1
+            } else {
+                1 - doubled
+            }
+        })
+        .sum();
+    Some((collinear + adjustment) as usize)
+}
+
+fn part1(lines: &[Line]) -> usize {
+    sweep_overlaps(lines, Mode::AxisAligned).unwrap()
 }
 
-fn part2(pairs: &[Pair]) -> usize {
-    overlaps(pairs)
+fn part2(lines: &[Line]) -> usize {
+    sweep_overlaps(lines, Mode::Diagonal).unwrap()
 }
 
 fn main() {
-    let pairs: Vec<_> = include_str!("../input.txt")
+    let lines: Vec<_> = include_str!("../input.txt")
         .lines()
         .map(|line| {
             let splits: Vec<i32> = line
-                .split(|c: char| !c.is_digit(10))
+                .split(|c: char| !c.is_ascii_digit())
                 .filter_map(|num| num.parse().ok())
                 .collect();
-            (splits[0], splits[1], splits[2], splits[3])
+            Line {
+                x0: splits[0],
+                y0: splits[1],
+                x1: splits[2],
+                y1: splits[3],
+            }
         })
         .collect();
-    println!("Part 1: {}", part1(&pairs));
-    println!("Part 2: {}", part2(&pairs));
+    println!("Part 1: {}", part1(&lines));
+    println!("Part 2: {}", part2(&lines));
+
+    let skipped = lines.iter().filter(|l| !Mode::Diagonal.allows(l)).count();
+    if skipped > 0 {
+        eprintln!(
+            "Warning: {} lines are neither axis-aligned nor 45°, skipped in part 2",
+            skipped
+        );
+        println!("Any angle: {}", overlaps(&lines, Mode::AnyAngle));
+    }
+
+    // `cargo run -- [--mode axis|diagonal|any] [--diagram] [--pgm <path>]`
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    let arg = |name: &str| {
+        args.iter()
+            .position(|arg| arg == name)
+            .map(|i| &args[i + 1])
+    };
+    let mode = match arg("--mode").map(String::as_str) {
+        Some("axis") => Mode::AxisAligned,
+        Some("any") => Mode::AnyAngle,
+        _ => Mode::Diagonal,
+    };
+    if args.iter().any(|arg| arg == "--diagram") {
+        let map = density(&lines, mode);
+        let (x0, y0, x1, y1) = diagram_bounds(&map);
+        if x1 - x0 < 100 && y1 - y0 < 100 {
+            print!("{}", diagram(&map));
+        } else {
+            eprintln!("Too large for a diagram, use --pgm instead");
+        }
+    }
+    if let Some(path) = arg("--pgm") {
+        std::fs::write(path, heatmap(&density(&lines, mode), 1024)).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_sweep_matches_map() {
+        let mut seed = 1u64;
+        let mut random = |n: i32| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((seed >> 33) % n as u64) as i32
+        };
+        for _ in 0..200 {
+            let lines: Vec<_> = (0..30)
+                .map(|_| {
+                    let (x0, y0, len) = (random(20), random(20), random(12));
+                    let (dx, dy) = [(1, 0), (0, 1), (1, 1), (1, -1), (0, 0)][random(5) as usize];
+                    Line {
+                        x0,
+                        y0,
+                        x1: x0 + dx * len,
+                        y1: y0 + dy * len,
+                    }
+                })
+                .collect();
+            for mode in [Mode::AxisAligned, Mode::Diagonal] {
+                assert_eq!(sweep_overlaps(&lines, mode), Some(overlaps(&lines, mode)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_any_angle_is_rejected() {
+        let line = Line {
+            x0: 0,
+            y0: 0,
+            x1: 6,
+            y1: 2,
+        };
+        assert_eq!(sweep_overlaps(&[line], Mode::AnyAngle), None);
+        assert_eq!(sweep_overlaps(&[line], Mode::Diagonal), Some(0));
+    }
+
+    #[test]
+    fn test_huge_coordinates() {
+        let line = |x0, y0, x1, y1| Line { x0, y0, x1, y1 };
+        let lines = [
+            line(0, 5, 1_000_000_000, 5),
+            line(500_000_000, 5, 0, 5),
+            line(7, 0, 7, 1_000_000_000),
+            line(0, 0, 1_000_000_000, 1_000_000_000),
+        ];
+        // Half of y = 5 is doubled, and the only new crossing is (7, 7)
+        assert_eq!(sweep_overlaps(&lines, Mode::AxisAligned), Some(500_000_001));
+        assert_eq!(sweep_overlaps(&lines, Mode::Diagonal), Some(500_000_002));
+    }
+
+    const EXAMPLE: [(i32, i32, i32, i32); 10] = [
+        (0, 9, 5, 9),
+        (8, 0, 0, 8),
+        (9, 4, 3, 4),
+        (2, 2, 2, 1),
+        (7, 0, 7, 4),
+        (6, 4, 2, 0),
+        (0, 9, 2, 9),
+        (3, 4, 1, 4),
+        (0, 0, 8, 8),
+        (5, 5, 8, 2),
+    ];
+
+    fn lines(ends: &[(i32, i32, i32, i32)]) -> Vec<Line> {
+        ends.iter()
+            .map(|&(x0, y0, x1, y1)| Line { x0, y0, x1, y1 })
+            .collect()
+    }
+
+    #[test]
+    fn test_diagram() {
+        let map = density(&lines(&EXAMPLE), Mode::Diagonal);
+        let expected = "\
+            1.1....11.\n\
+            .111...2..\n\
+            ..2.1.111.\n\
+            ...1.2.2..\n\
+            .112313211\n\
+            ...1.2....\n\
+            ..1...1...\n\
+            .1.....1..\n\
+            1.......1.\n\
+            222111....\n";
+        assert_eq!(diagram(&map), expected);
+        // The diagram starts at the origin even when the vents do not
+        let map = density(&lines(&[(2, 1, 3, 1)]), Mode::Diagonal);
+        assert_eq!(diagram(&map), "....\n..11\n");
+    }
+
+    #[test]
+    fn test_heatmap() {
+        let pgm = |ends: &[(i32, i32, i32, i32)]| {
+            let pgm = heatmap(&density(&lines(ends), Mode::Diagonal), 1024);
+            let rows: Vec<Vec<u32>> = pgm
+                .lines()
+                .skip(3)
+                .map(|row| row.split(' ').map(|p| p.parse().unwrap()).collect())
+                .collect();
+            (pgm.lines().take(3).collect::<Vec<_>>().join(" "), rows)
+        };
+
+        let (header, rows) = pgm(&EXAMPLE);
+        assert_eq!(header, "P2 10 10 255");
+        assert_eq!(rows.len(), 10);
+        assert!(rows.iter().all(|row| row.len() == 10));
+        assert_eq!((rows[4][3], rows[4][4]), (170, 255));
+
+        // Scaled to the vents, not to the origin
+        let (header, rows) = pgm(&[
+            (1_000_000, 1_000_000, 1_000_500, 1_000_000),
+            (1_000_000, 1_000_000, 1_000_000, 1_000_300),
+            (1_000_000, 1_000_300, 1_000_300, 1_000_000),
+        ]);
+        assert_eq!(header, "P2 501 301 255");
+        assert_eq!(rows.len(), 301);
+        assert!(rows.iter().all(|row| row.len() == 501));
+        assert_eq!(rows.iter().flatten().filter(|&&p| p > 0).count(), 1100);
+
+        // Boxes wider than the limit are shrunk to fit
+        let (header, rows) = pgm(&[(0, 0, 3000, 0), (0, 0, 0, 99)]);
+        assert_eq!(header, "P2 1001 34 255");
+        assert_eq!(rows.len(), 34);
+
+        assert_eq!(pgm(&[]).0, "P2 1 1 255");
+    }
+
+    #[test]
+    fn test_any_angle_raster() {
+        let line = |x0, y0, x1, y1| Line { x0, y0, x1, y1 };
+        let sorted = |line: Line| {
+            let mut points: Vec<_> = line.points().collect();
+            points.sort_unstable();
+            points
+        };
+        assert_eq!(sorted(line(0, 0, 2, 1)), [(0, 0), (1, 1), (2, 1)]);
+        assert_eq!(sorted(line(2, 1, 0, 0)), [(0, 0), (1, 1), (2, 1)]);
+        let vent = [line(0, 0, 2, 1), line(2, 1, 0, 0)];
+        assert_eq!(overlaps(&vent, Mode::AnyAngle), 3);
+
+        for (x0, y0, x1, y1) in [(0, 0, 6, 2), (3, 9, -4, 1), (5, 0, 0, 3), (-2, -7, 1, 4)] {
+            let forward = sorted(line(x0, y0, x1, y1));
+            assert_eq!(forward, sorted(line(x1, y1, x0, y0)));
+            // One point per step along the longer axis, ends included
+            let steps = (x1 - x0).abs().max((y1 - y0).abs());
+            assert_eq!(forward.len(), steps as usize + 1);
+            assert!(forward.contains(&(x0, y0)) && forward.contains(&(x1, y1)));
+            let path: Vec<_> = line(x0, y0, x1, y1).points().collect();
+            assert!(path
+                .windows(2)
+                .all(|w| (w[0].0 - w[1].0).abs() <= 1 && (w[0].1 - w[1].1).abs() <= 1));
+        }
+    }
 }
@@ -1,21 +1,192 @@
-fn simulate(data: &[usize], days: u32) -> u64 {
-    let mut counts = vec![0; 9];
-    for &fish in data {
-        counts[fish] += 1;
+use num_bigint::BigUint;
+use std::num::NonZeroU64;
+
+// How an age-structured population breeds: an individual whose timer runs out
+// restarts at `reset_timer` and adds a newborn at `spawn_timer`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Lifecycle {
+    reset_timer: usize,
+    spawn_timer: usize,
+}
+
+impl Lifecycle {
+    const LANTERNFISH: Self = Self {
+        reset_timer: 6,
+        spawn_timer: 8,
+    };
+
+    // The extra days a newborn waits before its first cycle
+    fn maturity_delay(&self) -> usize {
+        self.spawn_timer.saturating_sub(self.reset_timer)
+    }
+
+    fn buckets(&self) -> usize {
+        self.reset_timer.max(self.spawn_timer) + 1
+    }
+}
+
+// Counts are exact, since a school outgrows u64 after a few hundred days.
+#[derive(Clone, Debug)]
+struct Population {
+    lifecycle: Lifecycle,
+    counts: Vec<BigUint>,
+}
+
+impl Population {
+    fn new(lifecycle: Lifecycle, timers: &[usize]) -> Self {
+        let mut counts = vec![BigUint::ZERO; lifecycle.buckets()];
+        for &timer in timers {
+            assert!(timer < counts.len(), "timer {} out of range", timer);
+            counts[timer] += 1u8;
+        }
+        Self { lifecycle, counts }
+    }
+
+    fn step(&mut self) {
+        let spawning = std::mem::take(&mut self.counts[0]);
+        self.counts.rotate_left(1);
+        self.counts[self.lifecycle.reset_timer] += &spawning;
+        self.counts[self.lifecycle.spawn_timer] += spawning;
+    }
+
+    fn total(&self) -> BigUint {
+        self.counts.iter().sum()
     }
+}
+
+fn simulate(lifecycle: Lifecycle, data: &[usize], days: u32) -> BigUint {
+    let mut population = Population::new(lifecycle, data);
+    for _ in 0..days {
+        population.step();
+    }
+    population.total()
+}
+
+// Every bucket's count for day 0 through `days`.
+fn series(lifecycle: Lifecycle, data: &[usize], days: u32) -> Vec<Vec<BigUint>> {
+    let mut population = Population::new(lifecycle, data);
+    let mut series = vec![population.counts.clone()];
     for _ in 0..days {
-        counts.rotate_left(1);
-        counts[6] += counts[8];
+        population.step();
+        series.push(population.counts.clone());
+    }
+    series
+}
+
+// The first day on which the population reaches each threshold, if it does.
+fn crossings(series: &[Vec<BigUint>], thresholds: &[BigUint]) -> Vec<Option<usize>> {
+    let totals: Vec<BigUint> = series.iter().map(|counts| counts.iter().sum()).collect();
+    thresholds
+        .iter()
+        .map(|threshold| totals.iter().position(|total| total >= threshold))
+        .collect()
+}
+
+fn to_csv(series: &[Vec<BigUint>]) -> String {
+    let buckets = series.first().map_or(0, Vec::len);
+    let mut csv = "day".to_owned();
+    for timer in 0..buckets {
+        csv += &format!(",timer{}", timer);
+    }
+    csv += ",total\n";
+    for (day, counts) in series.iter().enumerate() {
+        let total: BigUint = counts.iter().sum();
+        let counts: Vec<_> = counts.iter().map(BigUint::to_string).collect();
+        csv += &format!("{},{},{}\n", day, counts.join(","), total);
+    }
+    csv
+}
+
+fn to_json(series: &[Vec<BigUint>]) -> String {
+    let days: Vec<_> = series
+        .iter()
+        .enumerate()
+        .map(|(day, counts)| {
+            let total: BigUint = counts.iter().sum();
+            let counts: Vec<_> = counts.iter().map(BigUint::to_string).collect();
+            format!(
+                "{{\"day\":{},\"counts\":[{}],\"total\":{}}}",
+                day,
+                counts.join(","),
+                total
+            )
+        })
+        .collect();
+    format!("[{}]", days.join(","))
+}
+
+type Matrix = Vec<Vec<BigUint>>;
+
+fn multiply(a: &Matrix, b: &Matrix, modulus: Option<&BigUint>) -> Matrix {
+    let n = a.len();
+    let mut product = vec![vec![BigUint::ZERO; n]; n];
+    for i in 0..n {
+        for k in 0..n {
+            if a[i][k] == BigUint::ZERO {
+                continue;
+            }
+            for j in 0..n {
+                product[i][j] += &a[i][k] * &b[k][j];
+            }
+        }
+        if let Some(modulus) = modulus {
+            product[i].iter_mut().for_each(|cell| *cell %= modulus);
+        }
+    }
+    product
+}
+
+// Column `j` holds where one individual with timer `j` ends up the next day.
+fn transition(lifecycle: Lifecycle) -> Matrix {
+    let n = lifecycle.buckets();
+    let mut matrix = vec![vec![BigUint::ZERO; n]; n];
+    for j in 1..n {
+        matrix[j - 1][j] = BigUint::from(1u8);
+    }
+    matrix[lifecycle.reset_timer][0] += 1u8;
+    matrix[lifecycle.spawn_timer][0] += 1u8;
+    matrix
+}
+
+// The population after `days`, in O(log days) matrix products. Exact counts
+// grow linearly in size with `days`, so very large day counts need a modulus.
+fn simulate_fast(
+    lifecycle: Lifecycle,
+    data: &[usize],
+    days: u64,
+    modulus: Option<NonZeroU64>,
+) -> BigUint {
+    let modulus = modulus.map(|modulus| BigUint::from(modulus.get()));
+    let n = lifecycle.buckets();
+    let mut power = (0..n)
+        .map(|i| (0..n).map(|j| BigUint::from((i == j) as u8)).collect())
+        .collect();
+    let mut base = transition(lifecycle);
+    let mut days = days;
+    while days > 0 {
+        if days & 1 == 1 {
+            power = multiply(&power, &base, modulus.as_ref());
+        }
+        base = multiply(&base, &base, modulus.as_ref());
+        days >>= 1;
+    }
+    let counts = Population::new(lifecycle, data).counts;
+    let total: BigUint = power
+        .iter()
+        .flat_map(|row| row.iter().zip(&counts).map(|(cell, count)| cell * count))
+        .sum();
+    match modulus {
+        Some(modulus) => total % modulus,
+        None => total,
     }
-    counts.iter().sum()
 }
 
-fn part1(data: &[usize]) -> u64 {
-    simulate(data, 80)
+fn part1(data: &[usize]) -> BigUint {
+    simulate(Lifecycle::LANTERNFISH, data, 80)
 }
 
-fn part2(data: &[usize]) -> u64 {
-    simulate(data, 256)
+fn part2(data: &[usize]) -> BigUint {
+    simulate(Lifecycle::LANTERNFISH, data, 256)
 }
 
 fn main() {
@@ -24,6 +195,128 @@ fn main() {
         .split(',')
         .map(|x| x.parse().unwrap())
         .collect();
+
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    // `cargo run -- --series <days> [--json] [--threshold <n>]...`
+    if let Some(i) = args.iter().position(|arg| arg == "--series") {
+        let series = series(Lifecycle::LANTERNFISH, &data, args[i + 1].parse().unwrap());
+        let thresholds: Vec<_> = args
+            .windows(2)
+            .filter(|pair| pair[0] == "--threshold")
+            .map(|pair| pair[1].parse().unwrap())
+            .collect();
+        if args.iter().any(|arg| arg == "--json") {
+            println!("{}", to_json(&series));
+        } else {
+            print!("{}", to_csv(&series));
+        }
+        for (threshold, day) in thresholds.iter().zip(crossings(&series, &thresholds)) {
+            match day {
+                Some(day) => eprintln!("Reaches {} on day {}", threshold, day),
+                None => eprintln!("Never reaches {}", threshold),
+            }
+        }
+        return;
+    }
+
     println!("Part 1: {}", part1(&data));
     println!("Part 2: {}", part2(&data));
+
+    // `cargo run -- --days <days> [--mod <prime>]`
+    if let Some(i) = args.iter().position(|arg| arg == "--days") {
+        let days = args[i + 1].parse().unwrap();
+        let modulus = match args.iter().position(|arg| arg == "--mod") {
+            Some(i) => match args.get(i + 1).map(|modulus| modulus.parse()) {
+                Some(Ok(modulus)) => Some(modulus),
+                _ => {
+                    eprintln!("Usage: --mod <modulus>, with a modulus of at least 1");
+                    return;
+                }
+            },
+            None => None,
+        };
+        let total = simulate_fast(Lifecycle::LANTERNFISH, &data, days, modulus);
+        match modulus {
+            Some(modulus) => println!("{} days: {} (mod {})", days, total, modulus),
+            None => println!("{} days: {}", days, total),
+        }
+    }
+
+    // `cargo run -- --lifecycle <reset timer>,<spawn timer> <days>`
+    if let Some(i) = args.iter().position(|arg| arg == "--lifecycle") {
+        let (reset, spawn) = args[i + 1].split_once(',').unwrap();
+        let lifecycle = Lifecycle {
+            reset_timer: reset.parse().unwrap(),
+            spawn_timer: spawn.parse().unwrap(),
+        };
+        let days = args[i + 2].parse().unwrap();
+        println!(
+            "{} days (maturity delay {}): {}",
+            days,
+            lifecycle.maturity_delay(),
+            simulate(lifecycle, &data, days)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_fast_matches_buckets() {
+        let data = [3, 4, 3, 1, 2];
+        for lifecycle in [
+            Lifecycle::LANTERNFISH,
+            Lifecycle {
+                reset_timer: 4,
+                spawn_timer: 9,
+            },
+        ] {
+            for days in [0, 1, 18, 80, 256, 700] {
+                let expected = simulate(lifecycle, &data, days);
+                assert_eq!(simulate_fast(lifecycle, &data, days as u64, None), expected);
+                assert_eq!(
+                    simulate_fast(
+                        lifecycle,
+                        &data,
+                        days as u64,
+                        NonZeroU64::new(1_000_000_007)
+                    ),
+                    expected % 1_000_000_007u32
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_series_past_u64() {
+        let data = [3, 4, 3, 1, 2];
+        let series = series(Lifecycle::LANTERNFISH, &data, 700);
+        assert_eq!(series.len(), 701);
+        for (day, counts) in series.iter().enumerate() {
+            let total: BigUint = counts.iter().sum();
+            assert_eq!(
+                total,
+                simulate_fast(Lifecycle::LANTERNFISH, &data, day as u64, None)
+            );
+        }
+        let total = |day: usize| series[day].iter().sum::<BigUint>();
+        assert!(total(700) > BigUint::from(u64::MAX));
+        let thresholds = [
+            BigUint::from(26u8),
+            BigUint::from(u64::MAX),
+            BigUint::from(u64::MAX) * 2u8,
+            total(700) + 1u8,
+        ];
+        let days = crossings(&series, &thresholds);
+        assert_eq!(days[0], Some(18));
+        for (threshold, day) in thresholds.iter().zip(&days).skip(1).take(2) {
+            let day = day.unwrap();
+            assert!(day > 450);
+            assert!(total(day - 1) < *threshold && total(day) >= *threshold);
+        }
+        assert_eq!(days[3], None);
+    }
 }
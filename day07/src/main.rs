@@ -1,23 +1,161 @@
-fn part1(data: &[i32]) -> i32 {
-    let median = data[data.len() / 2];
-    data.iter().map(|&pos| (pos - median).abs()).sum()
+use std::str::FromStr;
+
+// A crab is written as `position` or `position:weight`, where the weight
+// multiplies the fuel it burns.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Crab {
+    position: i64,
+    weight: i64,
+}
+
+impl FromStr for Crab {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (position, weight) = s.trim().split_once(':').unwrap_or((s.trim(), "1"));
+        let parse = |n: &str| n.parse().map_err(|_| format!("invalid crab {:?}", s));
+        let weight = parse(weight)?;
+        if weight < 0 {
+            return Err(format!("negative weight in {:?}", s));
+        }
+        Ok(Self {
+            position: parse(position)?,
+            weight,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Alignment {
+    position: i64,
+    cost: i64,
+}
+
+fn linear(distance: i64) -> i64 {
+    distance
+}
+
+fn triangular(distance: i64) -> i64 {
+    distance * (distance + 1) / 2
 }
 
-fn part2(data: &[i32]) -> i32 {
-    let mean = data.iter().sum::<i32>() / data.len() as i32;
-    data.iter()
-        .map(|&pos| (pos - mean).abs())
-        .map(|n| n * (n + 1) / 2)
+fn total_cost(crabs: &[Crab], position: i64, cost: impl Fn(i64) -> i64) -> i64 {
+    crabs
+        .iter()
+        .map(|crab| crab.weight * cost((crab.position - position).abs()))
         .sum()
 }
 
+fn range(crabs: &[Crab]) -> (i64, i64) {
+    let positions = crabs.iter().map(|crab| crab.position);
+    (positions.clone().min().unwrap(), positions.max().unwrap())
+}
+
+// With a convex cost per distance and non-negative weights the total cost is
+// convex in the position, so a binary search for the first position where the
+// cost stops falling finds the exact optimum. Ties go to the leftmost optimal
+// position.
+fn align(crabs: &[Crab], cost: impl Fn(i64) -> i64) -> Alignment {
+    let (mut lo, mut hi) = range(crabs);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if total_cost(crabs, mid, &cost) <= total_cost(crabs, mid + 1, &cost) {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+    Alignment {
+        position: lo,
+        cost: total_cost(crabs, lo, &cost),
+    }
+}
+
+// The total cost at every position between the outermost crabs.
+fn cost_curve(crabs: &[Crab], cost: impl Fn(i64) -> i64) -> String {
+    let (lo, hi) = range(crabs);
+    let mut csv = "position,cost\n".to_owned();
+    for position in lo..=hi {
+        csv += &format!("{},{}\n", position, total_cost(crabs, position, &cost));
+    }
+    csv
+}
+
+fn part1(crabs: &[Crab]) -> i64 {
+    align(crabs, linear).cost
+}
+
+fn part2(crabs: &[Crab]) -> i64 {
+    align(crabs, triangular).cost
+}
+
 fn main() {
-    let mut data: Vec<_> = include_str!("../input.txt")
+    let crabs: Vec<_> = include_str!("../input.txt")
         .trim()
         .split(',')
         .map(|x| x.parse().unwrap())
         .collect();
-    data.sort_unstable();
-    println!("Part 1: {}", part1(&data));
-    println!("Part 2: {}", part2(&data));
+
+    // `cargo run -- --curve linear|triangular` exports the cost curve as CSV
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    if let Some(i) = args.iter().position(|arg| arg == "--curve") {
+        match args.get(i + 1).map(String::as_str) {
+            Some("linear") => print!("{}", cost_curve(&crabs, linear)),
+            Some("triangular") => print!("{}", cost_curve(&crabs, triangular)),
+            _ => eprintln!("Usage: --curve linear|triangular"),
+        }
+        return;
+    }
+
+    println!("Part 1: {}", part1(&crabs));
+    println!("Part 2: {}", part2(&crabs));
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn crabs(s: &str) -> Vec<Crab> {
+        s.split(',').map(|crab| crab.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_floored_mean_misses() {
+        let crabs = crabs("16,1,2,0,4,2,7,1,2,14");
+        // The mean is 4.9, but the optimum is 5
+        let mean = crabs.iter().map(|crab| crab.position).sum::<i64>() / crabs.len() as i64;
+        assert_eq!(mean, 4);
+        assert_eq!(total_cost(&crabs, mean, triangular), 170);
+        assert_eq!(
+            align(&crabs, triangular),
+            Alignment {
+                position: 5,
+                cost: 168
+            }
+        );
+        assert_eq!(
+            align(&crabs, linear),
+            Alignment {
+                position: 2,
+                cost: 37
+            }
+        );
+    }
+
+    #[test]
+    fn test_flat_optimum_is_leftmost() {
+        for (crabs, position, cost) in [
+            (crabs("1,5"), 1, 4),
+            (crabs("9,0,3,6"), 3, 12),
+            (crabs("0:2,10:2"), 0, 20),
+            (crabs("0:3,10:1"), 0, 10),
+        ] {
+            assert_eq!(align(&crabs, linear), Alignment { position, cost });
+            let costs: Vec<_> = (range(&crabs).0..=range(&crabs).1)
+                .map(|p| total_cost(&crabs, p, linear))
+                .collect();
+            assert_eq!(costs.iter().min(), Some(&cost));
+        }
+    }
 }
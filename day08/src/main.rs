@@ -1,88 +1,373 @@
-fn part1(notes: &[Vec<Vec<String>>]) -> usize {
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, PartialEq)]
+enum NoteError {
+    InvalidWire(char),
+    DuplicateWire(String),
+    MissingOutput,
+    Conflict(String),
+    Ambiguous,
+}
+
+impl fmt::Display for NoteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidWire(ch) => write!(f, "invalid wire {:?}", ch),
+            Self::DuplicateWire(pattern) => write!(f, "pattern {} repeats a wire", pattern),
+            Self::MissingOutput => write!(f, "missing '|' before the output"),
+            Self::Conflict(pattern) => write!(
+                f,
+                "pattern {} cannot be wired consistently with the patterns before it",
+                pattern
+            ),
+            Self::Ambiguous => write!(f, "the output has more than one reading"),
+        }
+    }
+}
+
+impl std::error::Error for NoteError {}
+
+// Signals are bitmasks of wires, so one pattern contains another when
+// `a & b == b`.
+#[derive(Clone, Debug, PartialEq)]
+struct Note {
+    input: Vec<u32>,
+    output: Vec<u32>,
+}
+
+impl FromStr for Note {
+    type Err = NoteError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let (input, output) = line.split_once('|').ok_or(NoteError::MissingOutput)?;
+        let parse = |patterns: &str| -> Result<Vec<u32>, NoteError> {
+            patterns
+                .split_whitespace()
+                .map(|pattern| {
+                    let mut wires = 0;
+                    for ch in pattern.chars() {
+                        if !('a'..='g').contains(&ch) {
+                            return Err(NoteError::InvalidWire(ch));
+                        }
+                        let wire = 1 << (ch as u32 - 'a' as u32);
+                        if wires & wire != 0 {
+                            return Err(NoteError::DuplicateWire(pattern.to_owned()));
+                        }
+                        wires |= wire;
+                    }
+                    Ok(wires)
+                })
+                .collect()
+        };
+        Ok(Self {
+            input: parse(input)?,
+            output: parse(output)?,
+        })
+    }
+}
+
+fn part1(notes: &[Note]) -> usize {
     notes
         .iter()
-        .map(|note| &note[1])
-        .flatten()
-        .filter(|output| match output.len() {
-            2 | 3 | 4 | 7 => true,
-            _ => false,
-        })
+        .flat_map(|note| &note.output)
+        .filter(|output| matches!(output.count_ones(), 2 | 3 | 4 | 7))
         .count()
 }
 
-fn len_n_symbols(input: &[String], n: usize) -> Vec<&str> {
-    input
-        .iter()
-        .filter(|s| s.len() == n)
-        .map(|s| s.as_ref())
-        .collect()
+// A segment display: how many segments it has and which segments each glyph
+// lights, written as letters from `a`. A glyph's value is its index.
+struct Alphabet {
+    segments: usize,
+    glyphs: Vec<u32>,
 }
 
-fn len_n_symbol(input: &[String], n: usize) -> &str {
-    let symbols = len_n_symbols(input, n);
-    assert_eq!(symbols.len(), 1);
-    symbols[0]
+impl Alphabet {
+    fn new(segments: usize, glyphs: &[&str]) -> Self {
+        Self {
+            segments,
+            glyphs: glyphs.iter().map(|glyph| mask(glyph)).collect(),
+        }
+    }
+
+    fn seven_segment() -> Self {
+        Self::new(
+            7,
+            &[
+                "abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "acf", "abcdefg",
+                "abcdfg",
+            ],
+        )
+    }
+
+    fn full(&self) -> u32 {
+        (1 << self.segments) - 1
+    }
 }
 
-fn contains(container: &str, containee: &str) -> bool {
-    containee
+fn mask(symbol: &str) -> u32 {
+    symbol
         .chars()
-        .all(|letter| container.chars().any(|c| c == letter))
+        .fold(0, |mask, ch| mask | 1 << (ch as u32 - 'a' as u32))
 }
 
-fn find_symbol<'a>(candidates: &'a [&str], predicate: impl Fn(&&&str) -> bool) -> &'a str {
-    candidates.iter().find(predicate).unwrap()
+fn bits(mask: u32) -> impl Iterator<Item = usize> {
+    (0..32).filter(move |i| mask >> i & 1 == 1)
 }
 
-fn solve_note(note: &Vec<Vec<String>>) -> u32 {
-    let input = &note[0];
-    let output = &note[1];
-    let mut signals = vec![""; 10];
+fn letters(mask: u32) -> String {
+    bits(mask).map(|i| (b'a' + i as u8) as char).collect()
+}
 
-    signals[8] = "abcdefg"; // free symbol
-    signals[1] = len_n_symbol(input, 2); // cf
-    signals[7] = len_n_symbol(input, 3); // acf
-    signals[4] = len_n_symbol(input, 4); // bcdf
+// Narrows down which segments each wire can drive. A pattern of n wires has to
+// be one of the n-segment glyphs, so its wires can only drive segments of those
+// glyphs and the other wires only segments outside one of them. No two wires
+// drive the same segment. Returns false once some wire has nowhere left to go.
+fn propagate(alphabet: &Alphabet, patterns: &[u32], domains: &mut [u32]) -> bool {
+    let full = alphabet.full();
+    loop {
+        let before = domains.to_vec();
+        for &pattern in patterns {
+            let (mut inside, mut outside) = (0, 0);
+            for &glyph in &alphabet.glyphs {
+                let fits = glyph.count_ones() == pattern.count_ones()
+                    && (0..alphabet.segments).all(|w| {
+                        let allowed = if pattern >> w & 1 == 1 {
+                            glyph
+                        } else {
+                            !glyph & full
+                        };
+                        domains[w] & allowed != 0
+                    });
+                if fits {
+                    inside |= glyph;
+                    outside |= !glyph & full;
+                }
+            }
+            for (w, domain) in domains.iter_mut().enumerate() {
+                *domain &= if pattern >> w & 1 == 1 {
+                    inside
+                } else {
+                    outside
+                };
+            }
+        }
+        for w in 0..domains.len() {
+            if domains[w].count_ones() == 1 {
+                let taken = domains[w];
+                for (v, domain) in domains.iter_mut().enumerate() {
+                    if v != w {
+                        *domain &= !taken;
+                    }
+                }
+            }
+        }
+        if domains.contains(&0) {
+            return false;
+        }
+        if domains == before {
+            return true;
+        }
+    }
+}
 
-    let zero_six_nine = len_n_symbols(input, 6); // abcefg, abdefg, abcdfg
-    signals[6] = find_symbol(&zero_six_nine, |symbol| !contains(symbol, signals[1]));
-    signals[9] = find_symbol(&zero_six_nine, |symbol| contains(symbol, signals[4]));
-    signals[0] = find_symbol(&zero_six_nine, |&&symbol| {
-        symbol != signals[6] && symbol != signals[9]
-    });
+fn decode(alphabet: &Alphabet, mapping: &[usize], pattern: u32) -> Option<usize> {
+    let lit = bits(pattern).fold(0, |lit, w| lit | 1 << mapping[w]);
+    alphabet.glyphs.iter().position(|&glyph| glyph == lit)
+}
 
-    let two_three_five = len_n_symbols(input, 5); // acdeg, acdfg, abdfg
-    signals[3] = find_symbol(&two_three_five, |symbol| contains(symbol, signals[1]));
-    signals[5] = find_symbol(&two_three_five, |symbol| contains(signals[6], symbol));
-    signals[2] = find_symbol(&two_three_five, |&&symbol| {
-        symbol != signals[3] && symbol != signals[5]
-    });
+#[derive(Debug, PartialEq)]
+enum Decoded {
+    Unique(Vec<usize>),
+    Ambiguous,
+    Impossible,
+}
 
-    output.iter().fold(0, |total, next| {
-        total * 10 + signals.iter().position(|signal| signal == next).unwrap() as u32
-    })
+// Searches the wirings left after propagation, decoding `output` under each
+// one that explains every pattern. Stops as soon as two wirings disagree.
+fn search(
+    alphabet: &Alphabet,
+    patterns: &[u32],
+    output: &[u32],
+    mut domains: Vec<u32>,
+    found: &mut Option<Vec<usize>>,
+) -> bool {
+    if !propagate(alphabet, patterns, &mut domains) {
+        return true;
+    }
+    let open = (0..domains.len())
+        .filter(|&w| domains[w].count_ones() > 1)
+        .min_by_key(|&w| domains[w].count_ones());
+    if let Some(w) = open {
+        for segment in bits(domains[w]) {
+            let mut domains = domains.clone();
+            domains[w] = 1 << segment;
+            if !search(alphabet, patterns, output, domains, found) {
+                return false;
+            }
+        }
+        return true;
+    }
+    let mapping: Vec<_> = domains
+        .iter()
+        .map(|d| d.trailing_zeros() as usize)
+        .collect();
+    let valid = patterns
+        .iter()
+        .all(|&pattern| decode(alphabet, &mapping, pattern).is_some());
+    if !valid {
+        return true;
+    }
+    let digits: Vec<_> = output
+        .iter()
+        .map(|&pattern| decode(alphabet, &mapping, pattern).unwrap())
+        .collect();
+    match found {
+        Some(previous) => *previous == digits,
+        None => {
+            *found = Some(digits);
+            true
+        }
+    }
 }
 
-fn part2(data: &[Vec<Vec<String>>]) -> u32 {
-    data.iter().map(solve_note).sum()
+// Decodes `output` from whichever patterns were observed, the output included.
+fn solve(alphabet: &Alphabet, observed: &[u32], output: &[u32]) -> Decoded {
+    let mut patterns: Vec<_> = observed.iter().chain(output).copied().collect();
+    patterns.sort_unstable();
+    patterns.dedup();
+    let domains = vec![alphabet.full(); alphabet.segments];
+    let mut found = None;
+    if !search(alphabet, &patterns, output, domains, &mut found) {
+        return Decoded::Ambiguous;
+    }
+    match found {
+        Some(digits) => Decoded::Unique(digits),
+        None => Decoded::Impossible,
+    }
 }
 
-fn parse_note(note: &str) -> Vec<String> {
-    note.trim()
-        .split_whitespace()
-        .map(|s| {
-            let mut chars: Vec<char> = s.chars().collect();
-            chars.sort_unstable();
-            String::from_iter(chars)
-        })
-        .collect()
+// Output digits are read in base `glyphs.len()`, which is decimal for the
+// seven-segment digits. An impossible note is replayed one pattern at a time to
+// find the first pattern that cannot be wired up with the ones before it.
+fn solve_note(note: &Note) -> Result<u32, NoteError> {
+    let alphabet = Alphabet::seven_segment();
+    match solve(&alphabet, &note.input, &note.output) {
+        Decoded::Unique(digits) => Ok(digits.iter().fold(0, |total, &d| {
+            total * alphabet.glyphs.len() as u32 + d as u32
+        })),
+        Decoded::Ambiguous => Err(NoteError::Ambiguous),
+        Decoded::Impossible => {
+            let patterns: Vec<_> = note.input.iter().chain(&note.output).copied().collect();
+            let culprit = (1..=patterns.len())
+                .find(|&n| solve(&alphabet, &patterns[..n], &[]) == Decoded::Impossible)
+                .unwrap();
+            Err(NoteError::Conflict(letters(patterns[culprit - 1])))
+        }
+    }
+}
+
+// Only has an answer when every note decodes.
+fn part2(values: &[Result<u32, NoteError>]) -> Option<u32> {
+    values.iter().map(|value| value.as_ref().ok()).sum()
 }
 
 fn main() {
-    let data: Vec<_> = include_str!("../input.txt")
+    let notes: Vec<Note> = include_str!("../input.txt")
         .lines()
-        .map(|line| line.split('|').map(parse_note).collect())
+        .enumerate()
+        .map(|(i, line)| {
+            line.parse()
+                .unwrap_or_else(|e| panic!("note {}: {}", i + 1, e))
+        })
         .collect();
-    println!("Part 1: {}", part1(&data));
-    println!("Part 2: {}", part2(&data));
+    println!("Part 1: {}", part1(&notes));
+    let values: Vec<_> = notes.iter().map(solve_note).collect();
+    for (i, value) in values.iter().enumerate() {
+        if let Err(e) = value {
+            eprintln!("Note {}: {}", i + 1, e);
+        }
+    }
+    match part2(&values) {
+        Some(sum) => println!("Part 2: {}", sum),
+        None => println!("Part 2: no answer, see the notes above"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_partial_observations() {
+        let alphabet = Alphabet::seven_segment();
+        let note: Vec<_> = "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab"
+            .split_whitespace()
+            .map(mask)
+            .collect();
+        let output: Vec<_> = "cdfeb fcadb cdfeb cdbaf"
+            .split_whitespace()
+            .map(mask)
+            .collect();
+        assert_eq!(
+            solve(&alphabet, &note, &output),
+            Decoded::Unique(vec![5, 3, 5, 3])
+        );
+        // A lone five-segment pattern could be a 2, 3 or 5
+        assert_eq!(solve(&alphabet, &[], &output[..1]), Decoded::Ambiguous);
+        assert_eq!(
+            solve(&alphabet, &note[..6], &output),
+            Decoded::Unique(vec![5, 3, 5, 3])
+        );
+        assert_eq!(
+            solve(&alphabet, &[mask("ab"), mask("cd")], &[]),
+            Decoded::Impossible
+        );
+    }
+
+    #[test]
+    fn test_other_alphabets() {
+        // A 14-segment display showing a few letters
+        let alphabet = Alphabet::new(14, &["abcefgh", "adef", "abcdhjm", "adefg", "bcefgh"]);
+        let scramble = |glyph: &str| {
+            mask(
+                &glyph
+                    .chars()
+                    .map(|ch| (b'a' + (ch as u8 - b'a' + 5) % 14) as char)
+                    .collect::<String>(),
+            )
+        };
+        let observed: Vec<_> = ["abcefgh", "adef", "abcdhjm", "adefg", "bcefgh"]
+            .iter()
+            .map(|glyph| scramble(glyph))
+            .collect();
+        assert_eq!(
+            solve(&alphabet, &observed, &[observed[2], observed[0]]),
+            Decoded::Unique(vec![2, 0])
+        );
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let note = |line: &str| line.parse::<Note>();
+        assert_eq!(note("ab cd"), Err(NoteError::MissingOutput));
+        assert_eq!(note("ab xy | ab"), Err(NoteError::InvalidWire('x')));
+        let conflicting = note("ab abd abef cd | ab").unwrap();
+        assert_eq!(
+            solve_note(&conflicting),
+            Err(NoteError::Conflict("cd".to_owned()))
+        );
+        assert_eq!(
+            note("abcdefg ab abd | abcdefga"),
+            Err(NoteError::DuplicateWire("abcdefga".to_owned()))
+        );
+        assert_eq!(
+            note("abb | ab"),
+            Err(NoteError::DuplicateWire("abb".to_owned()))
+        );
+        assert_eq!(
+            solve_note(&note("abcde | abcde").unwrap()),
+            Err(NoteError::Ambiguous)
+        );
+    }
 }
@@ -1,14 +1,10 @@
-use std::collections::HashSet;
+use std::cmp::Ordering;
 
-fn part1(heights: &Vec<Vec<u32>>) -> u32 {
+fn part1(heights: &[Vec<u32>]) -> u32 {
     let mut risk = 0;
     for (i, row) in heights.iter().enumerate() {
         for (j, &height) in row.iter().enumerate() {
-            if (i == 0 || heights[i - 1][j] > height)
-                && (i == heights.len() - 1 || heights[i + 1][j] > height)
-                && (j == 0 || heights[i][j - 1] > height)
-                && (j == heights[0].len() - 1 || heights[i][j + 1] > height)
-            {
+            if is_low_point(heights, i, j) {
                 risk += height + 1
             }
         }
@@ -16,54 +12,160 @@ fn part1(heights: &Vec<Vec<u32>>) -> u32 {
     risk
 }
 
-fn basin_size(heights: &Vec<Vec<u32>>, i: usize, j: usize) -> usize {
-    let mut frontier = vec![(i, j)];
-    let mut seen = HashSet::new();
+// Union-find over cell indices, with union by size and path halving
+struct DisjointSet {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
 
-    while !frontier.is_empty() {
-        let (x, y) = frontier.pop().unwrap();
-        if seen.contains(&(x, y)) || heights[x][y] == 9 {
-            continue;
+impl DisjointSet {
+    fn new(n: usize) -> Self {
+        Self {
+            parent: (0..n).collect(),
+            size: vec![1; n],
         }
-        seen.insert((x, y));
-        let height = heights[x][y];
+    }
 
-        if x != 0 && heights[x - 1][y] > height {
-            frontier.push((x - 1, y));
-        }
-        if x != heights.len() - 1 && heights[x + 1][y] > height {
-            frontier.push((x + 1, y));
+    fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
         }
-        if y != 0 && heights[x][y - 1] > height {
-            frontier.push((x, y - 1));
+        x
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
         }
-        if y != heights[0].len() - 1 && heights[x][y + 1] > height {
-            frontier.push((x, y + 1));
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
         }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
     }
+}
 
-    seen.len()
+#[derive(Clone, Debug, PartialEq)]
+struct Basin {
+    size: usize,
+    // The lowest cell, first in reading order when several share the height
+    low_point: (usize, usize),
+    depths: (u32, u32),
+    // Minimum regions inside the basin: connected cells of one height with no
+    // lower neighbour. More than one means basins meet on a plateau with no 9
+    // between them. Flat sinks are the regions of more than one cell.
+    sinks: usize,
+    flat_sinks: usize,
 }
 
-fn part2(heights: &Vec<Vec<u32>>) -> usize {
-    let mut basin_sizes = vec![];
-    for (i, row) in heights.iter().enumerate() {
-        for (j, &height) in row.iter().enumerate() {
-            if (i == 0 || heights[i - 1][j] > height)
-                && (i == heights.len() - 1 || heights[i + 1][j] > height)
-                && (j == 0 || heights[i][j - 1] > height)
-                && (j == heights[0].len() - 1 || heights[i][j + 1] > height)
-            {
-                basin_sizes.push(basin_size(heights, i, j));
+fn is_low_point(heights: &[Vec<u32>], i: usize, j: usize) -> bool {
+    let height = heights[i][j];
+    (i == 0 || heights[i - 1][j] > height)
+        && (i == heights.len() - 1 || heights[i + 1][j] > height)
+        && (j == 0 || heights[i][j - 1] > height)
+        && (j == heights[0].len() - 1 || heights[i][j + 1] > height)
+}
+
+// Labels every cell with its basin in one pass: each cell below 9 joins its
+// right and lower neighbours below 9, and a second set joins neighbours of the
+// same height into floors. Basins that touch on a plateau merge into one, and
+// their stats count every floor with no way down as a sink. Basin IDs follow
+// the reading order of each basin's first cell, and 9s are unlabelled.
+fn label_basins(heights: &[Vec<u32>]) -> (Vec<Vec<Option<usize>>>, Vec<Basin>) {
+    let rows = heights.len();
+    let cols = heights.first().map_or(0, Vec::len);
+    let mut set = DisjointSet::new(rows * cols);
+    let mut floors = DisjointSet::new(rows * cols);
+    let mut lower = vec![];
+    for i in 0..rows {
+        for j in 0..cols {
+            let height = heights[i][j];
+            if height == 9 {
+                continue;
+            }
+            for (x, y) in [(i + 1, j), (i, j + 1)] {
+                if x == rows || y == cols || heights[x][y] == 9 {
+                    continue;
+                }
+                set.union(i * cols + j, x * cols + y);
+                match heights[x][y].cmp(&height) {
+                    Ordering::Equal => floors.union(i * cols + j, x * cols + y),
+                    Ordering::Less => lower.push(i * cols + j),
+                    Ordering::Greater => lower.push(x * cols + y),
+                }
             }
         }
     }
+    // A floor drains if any of its cells has a lower neighbour
+    let mut drains = vec![false; rows * cols];
+    for cell in lower {
+        drains[floors.find(cell)] = true;
+    }
+
+    let mut ids = vec![None; rows * cols];
+    let mut labels = vec![vec![None; cols]; rows];
+    let mut basins: Vec<Basin> = vec![];
+    for i in 0..rows {
+        for j in 0..cols {
+            let height = heights[i][j];
+            if height == 9 {
+                continue;
+            }
+            let root = set.find(i * cols + j);
+            let id = *ids[root].get_or_insert_with(|| {
+                basins.push(Basin {
+                    size: 0,
+                    low_point: (i, j),
+                    depths: (height, height),
+                    sinks: 0,
+                    flat_sinks: 0,
+                });
+                basins.len() - 1
+            });
+            labels[i][j] = Some(id);
+            let basin = &mut basins[id];
+            basin.size += 1;
+            if height < basin.depths.0 {
+                basin.low_point = (i, j);
+            }
+            basin.depths = (basin.depths.0.min(height), basin.depths.1.max(height));
+            let floor = floors.find(i * cols + j);
+            if floor == i * cols + j && !drains[floor] {
+                basin.sinks += 1;
+                basin.flat_sinks += (floors.size[floor] > 1) as usize;
+            }
+        }
+    }
+    (labels, basins)
+}
+
+// Basin sizes only mean something when every basin drains to one low point,
+// so merged or flat basins are an error rather than a guess.
+fn part2(heights: &[Vec<u32>]) -> Result<usize, String> {
+    let (_, basins) = label_basins(heights);
+    for basin in &basins {
+        if basin.sinks > 1 {
+            return Err(format!(
+                "the basin at {:?} joins {} low points on a plateau",
+                basin.low_point, basin.sinks
+            ));
+        }
+        if basin.flat_sinks > 0 {
+            return Err(format!(
+                "the basin at {:?} has a flat floor",
+                basin.low_point
+            ));
+        }
+    }
+    let mut basin_sizes: Vec<_> = basins.iter().map(|basin| basin.size).collect();
     basin_sizes.sort_unstable();
-    basin_sizes.iter().rev().take(3).product()
+    Ok(basin_sizes.iter().rev().take(3).product())
 }
 
 fn main() {
-    let heights = include_str!("../input.txt")
+    let heights: Vec<Vec<u32>> = include_str!("../input.txt")
         .lines()
         .map(|line| {
             line.trim()
@@ -73,5 +175,102 @@ fn main() {
         })
         .collect();
     println!("Part 1: {}", part1(&heights));
-    println!("Part 2: {}", part2(&heights));
+    match part2(&heights) {
+        Ok(product) => println!("Part 2: {}", product),
+        Err(e) => println!("Part 2: no answer, {}", e),
+    }
+
+    // `cargo run -- --basins` lists every basin and prints the label map
+    if std::env::args().any(|arg| arg == "--basins") {
+        let (labels, basins) = label_basins(&heights);
+        for (id, basin) in basins.iter().enumerate() {
+            let (low, high) = basin.depths;
+            println!(
+                "Basin {:>3}: size {:>3}, low point {:?}, depths {}..={}{}",
+                id,
+                basin.size,
+                basin.low_point,
+                low,
+                high,
+                match (basin.sinks, basin.flat_sinks) {
+                    (1, 0) => String::new(),
+                    (sinks, flat) => format!(" ({} sinks, {} flat, plateau)", sinks, flat),
+                }
+            );
+        }
+        for row in &labels {
+            let line: String = row
+                .iter()
+                .map(|label| match label {
+                    Some(id) => (b'a' + (id % 26) as u8) as char,
+                    None => '.',
+                })
+                .collect();
+            println!("{}", line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn grid(rows: &[&str]) -> Vec<Vec<u32>> {
+        rows.iter()
+            .map(|row| row.chars().map(|ch| ch.to_digit(10).unwrap()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_example_basins() {
+        let heights = grid(&[
+            "2199943210",
+            "3987894921",
+            "9856789892",
+            "8767896789",
+            "9899965678",
+        ]);
+        let (labels, basins) = label_basins(&heights);
+        let sizes: Vec<_> = basins.iter().map(|basin| basin.size).collect();
+        assert_eq!(sizes, [3, 9, 14, 9]);
+        assert!(basins.iter().all(|basin| basin.sinks == 1));
+        assert_eq!(basins[2].low_point, (2, 2));
+        assert_eq!(basins[2].depths, (5, 8));
+        assert_eq!(labels[0][2], None);
+        assert_eq!(labels[4][9], Some(3));
+        assert_eq!(part2(&heights), Ok(1134));
+    }
+
+    #[test]
+    fn test_plateau_merges_basins() {
+        // Two sinks joined by a ridge of 5s, and a basin with a flat floor
+        let heights = grid(&["15551", "99999", "33933"]);
+        let (labels, basins) = label_basins(&heights);
+        assert_eq!(basins.len(), 3);
+        assert_eq!(basins[0].size, 5);
+        assert_eq!(basins[0].sinks, 2);
+        assert_eq!(basins[0].low_point, (0, 0));
+        assert_eq!(basins[0].depths, (1, 5));
+        assert_eq!((basins[1].sinks, basins[1].flat_sinks), (1, 1));
+        assert_eq!(labels[2], [Some(1), Some(1), None, Some(2), Some(2)]);
+        assert_eq!(
+            part2(&heights),
+            Err("the basin at (0, 0) joins 2 low points on a plateau".to_owned())
+        );
+        // A flat floor that never drains into the strict low point beside it
+        let heights = grid(&["15225"]);
+        let (_, basins) = label_basins(&heights);
+        assert_eq!(basins.len(), 1);
+        assert_eq!((basins[0].sinks, basins[0].flat_sinks), (2, 1));
+        assert_eq!(
+            part2(&heights),
+            Err("the basin at (0, 0) joins 2 low points on a plateau".to_owned())
+        );
+        let flat = grid(&["33", "99", "12"]);
+        assert_eq!(
+            part2(&flat),
+            Err("the basin at (0, 0) has a flat floor".to_owned())
+        );
+    }
 }
@@ -0,0 +1,12 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use template::Packet;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(hex) = std::str::from_utf8(data) {
+        if let Ok(packet) = hex.parse::<Packet>() {
+            packet.version_sum();
+            packet.evaluate();
+        }
+    }
+});
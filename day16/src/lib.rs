@@ -0,0 +1,199 @@
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    InvalidHex(char),
+    UnexpectedEnd,
+    LengthMismatch { expected: usize, found: usize },
+    Arity { opcode: u64, packets: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidHex(ch) => write!(f, "invalid hex digit {:?}", ch),
+            Self::UnexpectedEnd => write!(f, "unexpected end of transmission"),
+            Self::LengthMismatch { expected, found } => write!(
+                f,
+                "sub-packets should end at bit {} but ended at bit {}",
+                expected, found
+            ),
+            Self::Arity { opcode, packets } => {
+                write!(f, "operator {} cannot take {} sub-packets", opcode, packets)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+enum InnerPacket {
+    Literal(u64),
+    Operator { opcode: u64, packets: Vec<Packet> },
+}
+
+pub struct Packet {
+    version: u64,
+    inner: InnerPacket,
+}
+
+struct PacketParser {
+    binary: String,
+    i: usize,
+}
+
+impl PacketParser {
+    fn read(&mut self, take: usize) -> Result<u64, ParseError> {
+        let bits = self
+            .binary
+            .get(self.i..self.i + take)
+            .ok_or(ParseError::UnexpectedEnd)?;
+        let value = u64::from_str_radix(bits, 2).unwrap();
+        self.i += take;
+        Ok(value)
+    }
+
+    fn parse_literal(&mut self, version: u64) -> Result<Packet, ParseError> {
+        let mut literal = 0;
+        while self.read(1)? == 1 {
+            literal = (literal << 4) | self.read(4)?;
+        }
+        literal = (literal << 4) | self.read(4)?;
+        Ok(Packet {
+            version,
+            inner: InnerPacket::Literal(literal),
+        })
+    }
+
+    fn parse_operator(&mut self, version: u64, opcode: u64) -> Result<Packet, ParseError> {
+        let mut packets = vec![];
+        if self.read(1)? == 1 {
+            for _ in 0..self.read(11)? {
+                packets.push(self.parse()?)
+            }
+        } else {
+            let ending_bit = self.read(15)? as usize + self.i;
+            while self.i < ending_bit {
+                packets.push(self.parse()?);
+            }
+            if self.i != ending_bit {
+                return Err(ParseError::LengthMismatch {
+                    expected: ending_bit,
+                    found: self.i,
+                });
+            }
+        }
+        let arity_ok = match opcode {
+            0 | 1 => true,
+            2 | 3 => !packets.is_empty(),
+            _ => packets.len() == 2,
+        };
+        if !arity_ok {
+            return Err(ParseError::Arity {
+                opcode,
+                packets: packets.len(),
+            });
+        }
+        Ok(Packet {
+            version,
+            inner: InnerPacket::Operator { opcode, packets },
+        })
+    }
+
+    fn parse(&mut self) -> Result<Packet, ParseError> {
+        let version = self.read(3)?;
+        let opcode = self.read(3)?;
+
+        if opcode == 4 {
+            self.parse_literal(version)
+        } else {
+            self.parse_operator(version, opcode)
+        }
+    }
+}
+
+impl FromStr for Packet {
+    type Err = ParseError;
+
+    fn from_str(hex: &str) -> Result<Self, Self::Err> {
+        let binary = hex
+            .trim()
+            .chars()
+            .map(|ch| {
+                ch.to_digit(16)
+                    .map(|digit| format!("{:04b}", digit))
+                    .ok_or(ParseError::InvalidHex(ch))
+            })
+            .collect::<Result<_, _>>()?;
+        let mut parser = PacketParser { binary, i: 0 };
+        parser.parse()
+    }
+}
+
+impl Packet {
+    pub fn evaluate(&self) -> u64 {
+        match &self.inner {
+            InnerPacket::Literal(v) => *v,
+            InnerPacket::Operator { opcode, packets } => {
+                let mut packets = packets.iter().map(|p| p.evaluate());
+                match opcode {
+                    0 => packets.fold(0, u64::wrapping_add),
+                    1 => packets.fold(1, u64::wrapping_mul),
+                    2 => packets.min().unwrap(),
+                    3 => packets.max().unwrap(),
+                    5..=7 => {
+                        let (a, b) = (packets.next(), packets.next());
+                        (match opcode {
+                            5 => a > b,
+                            6 => a < b,
+                            7 => a == b,
+                            _ => unreachable!(),
+                        }) as u64
+                    }
+                    _ => unreachable!(),
+                }
+            }
+        }
+    }
+
+    pub fn version_sum(&self) -> u64 {
+        self.version
+            + if let InnerPacket::Operator { packets, .. } = &self.inner {
+                packets.iter().map(|p| p.version_sum()).sum::<u64>()
+            } else {
+                0
+            }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_parse_errors() {
+        for (s, e) in [
+            ("", ParseError::UnexpectedEnd),
+            ("8A00Z", ParseError::InvalidHex('Z')),
+            ("8A00", ParseError::UnexpectedEnd),
+            ("D2FE2", ParseError::UnexpectedEnd),
+            (
+                "3800334529120",
+                ParseError::LengthMismatch {
+                    expected: 34,
+                    found: 49,
+                },
+            ),
+            (
+                "16004408",
+                ParseError::Arity {
+                    opcode: 5,
+                    packets: 1,
+                },
+            ),
+        ] {
+            assert_eq!(Packet::from_str(s).err(), Some(e), "{:?}", s);
+        }
+    }
+}
@@ -0,0 +1,14 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use template::FishNum;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(s) = std::str::from_utf8(data) {
+        if let Ok(num) = s.parse::<FishNum>() {
+            assert_eq!(
+                num.to_string().parse::<FishNum>().unwrap().to_string(),
+                num.to_string()
+            );
+        }
+    }
+});
@@ -0,0 +1,16 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use template::FishNum;
+
+fuzz_target!(|data: &[u8]| {
+    let nums: Vec<FishNum> = match std::str::from_utf8(data) {
+        Ok(s) => s.lines().filter_map(|line| line.parse().ok()).collect(),
+        Err(_) => return,
+    };
+    if nums.len() < 2 || !nums.iter().all(FishNum::is_reduced) {
+        return;
+    }
+    let sum = FishNum::sum(&nums);
+    assert!(sum.is_reduced());
+    sum.magnitude();
+});
@@ -0,0 +1,375 @@
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    Unexpected { found: char, at: usize },
+    UnexpectedEnd,
+    InvalidNumber { at: usize },
+    TrailingInput { at: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Unexpected { found, at } => write!(f, "unexpected {:?} at {}", found, at),
+            Self::UnexpectedEnd => write!(f, "unexpected end of number"),
+            Self::InvalidNumber { at } => write!(f, "invalid regular number at {}", at),
+            Self::TrailingInput { at } => write!(f, "trailing input at {}", at),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Clone, Debug)]
+pub enum FishNum {
+    Value(u32),
+    Pair(Box<FishNum>, Box<FishNum>),
+}
+
+struct FishParser<'a> {
+    i: usize,
+    s: &'a [u8],
+}
+
+impl<'a> FishParser<'a> {
+    fn new(s: &'a str) -> Self {
+        Self {
+            i: 0,
+            s: s.as_bytes(),
+        }
+    }
+
+    fn peek(&self) -> Result<u8, ParseError> {
+        self.s.get(self.i).copied().ok_or(ParseError::UnexpectedEnd)
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<(), ParseError> {
+        match self.peek()? {
+            b if b == expected => {
+                self.i += 1;
+                Ok(())
+            }
+            b => Err(ParseError::Unexpected {
+                found: b as char,
+                at: self.i,
+            }),
+        }
+    }
+
+    fn parse(&mut self) -> Result<FishNum, ParseError> {
+        if self.peek()? == b'[' {
+            self.i += 1;
+            let left = self.parse()?;
+            self.expect(b',')?;
+            let right = self.parse()?;
+            self.expect(b']')?;
+            Ok(FishNum::Pair(Box::new(left), Box::new(right)))
+        } else {
+            let digits = self.s[self.i..]
+                .iter()
+                .take_while(|b| b.is_ascii_digit())
+                .count();
+            if digits == 0 {
+                return Err(ParseError::Unexpected {
+                    found: self.peek()? as char,
+                    at: self.i,
+                });
+            }
+            let value = std::str::from_utf8(&self.s[self.i..self.i + digits])
+                .unwrap()
+                .parse()
+                .map_err(|_| ParseError::InvalidNumber { at: self.i })?;
+            self.i += digits;
+            Ok(FishNum::Value(value))
+        }
+    }
+}
+
+impl FromStr for FishNum {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = FishParser::new(s.trim());
+        let num = parser.parse()?;
+        if parser.i < parser.s.len() {
+            return Err(ParseError::TrailingInput { at: parser.i });
+        }
+        Ok(num)
+    }
+}
+
+impl FishNum {
+    // Addition is only defined on reduced numbers: no pair nested inside four
+    // pairs and no regular number above 9.
+    pub fn is_reduced(&self) -> bool {
+        self.is_reduced_helper(0)
+    }
+
+    fn is_reduced_helper(&self, depth: usize) -> bool {
+        match self {
+            Self::Value(v) => *v < 10,
+            Self::Pair(a, b) => {
+                depth < 4 && a.is_reduced_helper(depth + 1) && b.is_reduced_helper(depth + 1)
+            }
+        }
+    }
+
+    pub fn magnitude(&self) -> u32 {
+        match self {
+            Self::Value(v) => *v,
+            Self::Pair(l, r) => 3 * l.magnitude() + 2 * r.magnitude(),
+        }
+    }
+
+    fn add_left(&self, value: u32) -> Self {
+        match self {
+            Self::Value(v) => Self::Value(v + value),
+            Self::Pair(a, b) => Self::Pair(Box::new(a.add_left(value)), b.clone()),
+        }
+    }
+
+    fn add_right(&self, value: u32) -> Self {
+        match self {
+            Self::Value(v) => Self::Value(v + value),
+            Self::Pair(a, b) => Self::Pair(a.clone(), Box::new(b.add_right(value))),
+        }
+    }
+
+    fn explode_helper(&self, depth: usize) -> Option<(Option<u32>, Self, Option<u32>)> {
+        if let Self::Pair(a, b) = self {
+            if depth > 3 {
+                let (a, b) = (a.clone(), b.clone());
+                if let (Self::Value(a), Self::Value(b)) = (*a, *b) {
+                    Some((Some(a), Self::Value(0), Some(b)))
+                } else {
+                    None
+                }
+            } else if let Some((l, new_a, r)) = a.explode_helper(depth + 1) {
+                if let Some(v) = r {
+                    Some((
+                        l,
+                        Self::Pair(Box::new(new_a), Box::new(b.add_left(v))),
+                        None,
+                    ))
+                } else {
+                    Some((l, Self::Pair(Box::new(new_a), b.clone()), r))
+                }
+            } else if let Some((l, new_b, r)) = b.explode_helper(depth + 1) {
+                if let Some(v) = l {
+                    Some((
+                        None,
+                        Self::Pair(Box::new(a.add_right(v)), Box::new(new_b)),
+                        r,
+                    ))
+                } else {
+                    Some((l, Self::Pair(a.clone(), Box::new(new_b)), r))
+                }
+            } else {
+                None
+            }
+        } else {
+            None
+        }
+    }
+
+    pub fn explode(&self) -> Option<Self> {
+        if let Some((_, n, _)) = self.explode_helper(0) {
+            Some(n)
+        } else {
+            None
+        }
+    }
+
+    pub fn split(&self) -> Option<Self> {
+        match self {
+            Self::Value(a) if *a > 9 => Some(Self::Pair(
+                Box::new(Self::Value(a / 2)),
+                Box::new(Self::Value(a - a / 2)),
+            )),
+            Self::Value(_) => None,
+            Self::Pair(a, b) => {
+                if let Some(a) = a.split() {
+                    Some(Self::Pair(Box::new(a), b.clone()))
+                } else if let Some(b) = b.split() {
+                    Some(Self::Pair(a.clone(), Box::new(b)))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    fn reduce(mut self) -> Self {
+        loop {
+            if let Some(f) = self.explode() {
+                self = f;
+            } else if let Some(f) = self.split() {
+                self = f;
+            } else {
+                return self;
+            }
+        }
+    }
+
+    pub fn add(&self, that: &Self) -> Self {
+        Self::Pair(Box::new(self.clone()), Box::new(that.clone())).reduce()
+    }
+
+    pub fn sum(nums: &[Self]) -> Self {
+        let mut sum = nums[0].clone();
+        nums.iter().for_each(|num| sum = sum.add(num));
+        sum
+    }
+}
+
+impl fmt::Display for FishNum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Value(a) => write!(f, "{}", a),
+            Self::Pair(a, b) => {
+                write!(f, "[")?;
+                write!(f, "{}", a)?;
+                write!(f, ",")?;
+                write!(f, "{}", b)?;
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_magnitudes() {
+        for (s, m) in [
+            ("[[1,2],[[3,4],5]]", 143),
+            (
+                "[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]",
+                3488,
+            ),
+        ] {
+            assert_eq!(FishNum::from_str(s).unwrap().magnitude(), m);
+        }
+    }
+
+    #[test]
+    fn test_sum() {
+        for (nums, sum) in [(
+            vec![
+                "[[[0,[4,5]],[0,0]],[[[4,5],[2,6]],[9,5]]]",
+                "[7,[[[3,7],[4,3]],[[6,3],[8,8]]]]",
+                "[[2,[[0,8],[3,4]]],[[[6,7],1],[7,[1,6]]]]",
+                "[[[[2,4],7],[6,[0,5]]],[[[6,8],[2,8]],[[2,1],[4,5]]]]",
+                "[7,[5,[[3,8],[1,4]]]]",
+                "[[2,[2,2]],[8,[8,1]]]",
+                "[2,9]",
+                "[1,[[[9,3],9],[[9,0],[0,7]]]]",
+                "[[[5,[7,4]],7],1]",
+                "[[[[4,2],2],6],[8,7]]",
+            ],
+            "[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]",
+        )] {
+            let nums: Vec<_> = nums.iter().map(|s| FishNum::from_str(s).unwrap()).collect();
+            assert_eq!(format!("{}", FishNum::sum(&nums)), sum);
+        }
+    }
+
+    #[test]
+    fn test_add() {
+        for (a, b, s) in [
+            ("[1,2]", "[[3,4],5]", "[[1,2],[[3,4],5]]"),
+            (
+                "[[[[4,3],4],4],[7,[[8,4],9]]]",
+                "[1,1]",
+                "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]",
+            ),
+            (
+                "[[[0,[4,5]],[0,0]],[[[4,5],[2,6]],[9,5]]]",
+                "[7,[[[3,7],[4,3]],[[6,3],[8,8]]]]",
+                "[[[[4,0],[5,4]],[[7,7],[6,0]]],[[8,[7,7]],[[7,9],[5,0]]]]",
+            ),
+            (
+                "[[[[4,0],[5,4]],[[7,7],[6,0]]],[[8,[7,7]],[[7,9],[5,0]]]]",
+                "[[2,[[0,8],[3,4]]],[[[6,7],1],[7,[1,6]]]]",
+                "[[[[6,7],[6,7]],[[7,7],[0,7]]],[[[8,7],[7,7]],[[8,8],[8,0]]]]",
+            ),
+            (
+                "[[[[6,7],[6,7]],[[7,7],[0,7]]],[[[8,7],[7,7]],[[8,8],[8,0]]]]",
+                "[[[[2,4],7],[6,[0,5]]],[[[6,8],[2,8]],[[2,1],[4,5]]]]",
+                "[[[[7,0],[7,7]],[[7,7],[7,8]]],[[[7,7],[8,8]],[[7,7],[8,7]]]]",
+            ),
+            (
+                "[[[[7,0],[7,7]],[[7,7],[7,8]]],[[[7,7],[8,8]],[[7,7],[8,7]]]]",
+                "[7,[5,[[3,8],[1,4]]]]",
+                "[[[[7,7],[7,8]],[[9,5],[8,7]]],[[[6,8],[0,8]],[[9,9],[9,0]]]]",
+            ),
+            (
+                "[[[[7,7],[7,8]],[[9,5],[8,7]]],[[[6,8],[0,8]],[[9,9],[9,0]]]]",
+                "[[2,[2,2]],[8,[8,1]]]",
+                "[[[[6,6],[6,6]],[[6,0],[6,7]]],[[[7,7],[8,9]],[8,[8,1]]]]",
+            ),
+            (
+                "[[[[6,6],[6,6]],[[6,0],[6,7]]],[[[7,7],[8,9]],[8,[8,1]]]]",
+                "[2,9]",
+                "[[[[6,6],[7,7]],[[0,7],[7,7]]],[[[5,5],[5,6]],9]]",
+            ),
+            (
+                "[[[[6,6],[7,7]],[[0,7],[7,7]]],[[[5,5],[5,6]],9]]",
+                "[1,[[[9,3],9],[[9,0],[0,7]]]]",
+                "[[[[7,8],[6,7]],[[6,8],[0,8]]],[[[7,7],[5,0]],[[5,5],[5,6]]]]",
+            ),
+            (
+                "[[[[7,8],[6,7]],[[6,8],[0,8]]],[[[7,7],[5,0]],[[5,5],[5,6]]]]",
+                "[[[5,[7,4]],7],1]",
+                "[[[[7,7],[7,7]],[[8,7],[8,7]]],[[[7,0],[7,7]],9]]",
+            ),
+            (
+                "[[[[7,7],[7,7]],[[8,7],[8,7]]],[[[7,0],[7,7]],9]]",
+                "[[[[4,2],2],6],[8,7]]",
+                "[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]",
+            ),
+        ] {
+            let sum = FishNum::from_str(a)
+                .unwrap()
+                .add(&FishNum::from_str(b).unwrap());
+            assert_eq!(format!("{}", sum), s);
+        }
+    }
+
+    #[test]
+    fn test_explode() {
+        for (s, e) in [("[[[[[9,8],1],2],3],4]", "[[[[0,9],2],3],4]")] {
+            let exploded = FishNum::from_str(s).unwrap().explode().unwrap();
+            assert_eq!(format!("{}", exploded), e);
+        }
+    }
+
+    #[test]
+    fn test_split() {
+        for (s, e) in [(
+            "[[[[0,7],4],[15,[0,13]]],[1,1]]",
+            "[[[[0,7],4],[[7,8],[0,13]]],[1,1]]",
+        )] {
+            let split = FishNum::from_str(s).unwrap().split().unwrap();
+            assert_eq!(format!("{}", split), e);
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for (s, e) in [
+            ("", ParseError::UnexpectedEnd),
+            ("[1,2", ParseError::UnexpectedEnd),
+            ("[1;2]", ParseError::Unexpected { found: ';', at: 2 }),
+            ("[,2]", ParseError::Unexpected { found: ',', at: 1 }),
+            ("[1,2]]", ParseError::TrailingInput { at: 5 }),
+            ("[1,99999999999]", ParseError::InvalidNumber { at: 3 }),
+        ] {
+            assert_eq!(FishNum::from_str(s).unwrap_err(), e);
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use shared::stats;
 use std::collections::{HashMap, HashSet};
 
 type Point = (isize, isize, isize);
@@ -67,6 +68,8 @@ fn find_match(
         .iter()
         .filter(|(fprint, _)| known_fingerprints.contains_key(fprint))
         .collect();
+    stats::bump("match attempts");
+    stats::add("fingerprint matches", matching_fprints.len() as u64);
 
     if matching_fprints.len() < 66 {
         return None;
@@ -75,6 +78,7 @@ fn find_match(
     for (fprint, (u0, u1)) in matching_fprints {
         for (k0, k1) in known_fingerprints.get(fprint).unwrap() {
             for rotation in 0..24 {
+                stats::bump("rotations tried");
                 let translation = sub(*k0, rotate(*u0, rotation));
                 if translation == sub(*k1, rotate(*u1, rotation)) {
                     let transformed_beacons = beacons
@@ -135,6 +139,7 @@ fn solve(scanners: &[Vec<Point>]) -> (Vec<Point>, HashSet<Point>) {
 }
 
 fn main() {
+    stats::init();
     let scanners: Vec<_> = include_str!("../input.txt")
         .split("\n\n")
         .map(|block| {
@@ -150,5 +155,7 @@ fn main() {
         .collect();
     let solution = solve(&scanners);
     println!("Part 1: {}", part1(&solution));
+    stats::report();
     println!("Part 2: {}", part2(&solution));
+    stats::report();
 }
@@ -1,24 +1,27 @@
-use cached::proc_macro::cached;
+use shared::Memo;
 
 static ROLLS: &[(u8, u128)] = &[(3, 1), (4, 3), (5, 6), (6, 7), (7, 6), (8, 3), (9, 1)];
 
-#[cached]
-fn universes(this_s: u8, that_s: u8, this_p: u8, that_p: u8) -> (u128, u128) {
+type Universes = Memo<(u8, u8, u8, u8), (u128, u128)>;
+
+fn universes(memo: &mut Universes, this_s: u8, that_s: u8, this_p: u8, that_p: u8) -> (u128, u128) {
     if that_s > 20 {
         return (0, 1);
     }
-    let (mut wins, mut losses) = (0, 0);
-    for &(roll, freq) in ROLLS {
-        let new_p = (this_p + roll - 1) % 10 + 1;
-        let (new_losses, new_wins) = universes(that_s, this_s + new_p, that_p, new_p);
-        wins += new_wins * freq;
-        losses += new_losses * freq;
-    }
-    (wins, losses)
+    memo.get_or_insert_with((this_s, that_s, this_p, that_p), |memo| {
+        let (mut wins, mut losses) = (0, 0);
+        for &(roll, freq) in ROLLS {
+            let new_p = (this_p + roll - 1) % 10 + 1;
+            let (new_losses, new_wins) = universes(memo, that_s, this_s + new_p, that_p, new_p);
+            wins += new_wins * freq;
+            losses += new_losses * freq;
+        }
+        (wins, losses)
+    })
 }
 
 fn part2(one: u32, two: u32) -> u128 {
-    let (one, two) = universes(0, 0, one as u8, two as u8);
+    let (one, two) = universes(&mut Memo::new(), 0, 0, one as u8, two as u8);
     one.max(two)
 }
 
@@ -1,3 +1,5 @@
+use shared::stats;
+
 #[derive(Clone, Copy, Debug)]
 struct Instruction {
     on: bool,
@@ -104,12 +106,16 @@ fn insert_region(mut regions: Vec<Region>, region: Region, on: bool) -> Vec<Regi
         for old_region in &regions {
             new_regions = insert_region(new_regions, *old_region, false);
         }
+        stats::add("regions created", new_regions.len() as u64);
         regions.extend(new_regions);
         regions
     } else {
         regions
             .iter()
-            .flat_map(|old_region| old_region.diff(&region))
+            .flat_map(|old_region| {
+                stats::bump("region diffs");
+                old_region.diff(&region)
+            })
             .collect()
     }
 }
@@ -137,6 +143,7 @@ fn part2(instructions: &Vec<Instruction>) -> isize {
 }
 
 fn main() {
+    stats::init();
     let instructions: Vec<_> = include_str!("../input.txt")
         .lines()
         .map(|line| {
@@ -158,5 +165,7 @@ fn main() {
         })
         .collect();
     println!("Part 1: {}", part1(&instructions));
+    stats::report();
     println!("Part 1: {}", part2(&instructions));
+    stats::report();
 }
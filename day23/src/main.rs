@@ -1,3 +1,4 @@
+use shared::stats;
 use std::cmp::Reverse;
 use std::collections::{BinaryHeap, HashSet};
 
@@ -92,8 +93,10 @@ fn solve(grid: Vec<Vec<usize>>) -> usize {
     while !heap.is_empty() {
         let Reverse((cost, grid)) = heap.pop().unwrap();
         if !seen.insert(grid.clone()) {
+            stats::bump("stale pops");
             continue;
         }
+        stats::bump("states expanded");
         if solved(&grid) {
             return cost;
         }
@@ -103,6 +106,7 @@ fn solve(grid: Vec<Vec<usize>>) -> usize {
                 continue;
             }
             let new_cost = cost + move_cost(m, &grid);
+            stats::bump("heap pushes");
             heap.push(Reverse((new_cost, new_grid)));
         }
     }
@@ -127,6 +131,7 @@ fn part2(grid: &[Vec<usize>]) -> usize {
 }
 
 fn main() {
+    stats::init();
     let mut grid = vec![vec![0]; 11];
     for line in include_str!("../input.txt").lines().skip(2) {
         for (i, ch) in line.chars().filter(char::is_ascii_uppercase).enumerate() {
@@ -134,5 +139,7 @@ fn main() {
         }
     }
     println!("Part 1: {}", part1(&grid));
+    stats::report();
     println!("Part 2: {}", part2(&grid));
+    stats::report();
 }
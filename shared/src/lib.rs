@@ -0,0 +1,4 @@
+mod memo;
+pub mod stats;
+
+pub use memo::{Memo, MemoStats};
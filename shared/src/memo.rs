@@ -0,0 +1,144 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::hash::Hash;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MemoStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub len: usize,
+}
+
+impl fmt::Display for MemoStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let lookups = self.hits + self.misses;
+        let rate = if lookups == 0 {
+            0.0
+        } else {
+            100.0 * self.hits as f64 / lookups as f64
+        };
+        write!(
+            f,
+            "{} hits, {} misses ({:.1}% hit rate), {} evictions, {} entries",
+            self.hits, self.misses, rate, self.evictions, self.len
+        )
+    }
+}
+
+// A cache for one run of a recursive function. Unlike a global cache it is
+// dropped with the run, and it can be bounded: once `capacity` entries are
+// stored the oldest one is evicted for each new entry.
+pub struct Memo<K, V> {
+    cache: HashMap<K, V>,
+    order: VecDeque<K>,
+    capacity: Option<usize>,
+    stats: MemoStats,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Memo<K, V> {
+    pub fn new() -> Self {
+        Self {
+            cache: HashMap::new(),
+            order: VecDeque::new(),
+            capacity: None,
+            stats: MemoStats::default(),
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity: Some(capacity),
+            ..Self::new()
+        }
+    }
+
+    // Returns the cached value for `key`, or computes it with `f`. The memo is
+    // passed back into `f` so it can recurse through the same cache.
+    pub fn get_or_insert_with(&mut self, key: K, f: impl FnOnce(&mut Self) -> V) -> V {
+        if let Some(value) = self.cache.get(&key) {
+            self.stats.hits += 1;
+            return value.clone();
+        }
+        self.stats.misses += 1;
+        let value = f(self);
+        self.insert(key, value.clone());
+        value
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        match self.capacity {
+            Some(0) => return,
+            Some(capacity) if self.cache.len() >= capacity => {
+                if let Some(oldest) = self.order.pop_front() {
+                    self.cache.remove(&oldest);
+                    self.stats.evictions += 1;
+                }
+            }
+            _ => {}
+        }
+        if self.capacity.is_some() {
+            self.order.push_back(key.clone());
+        }
+        self.cache.insert(key, value);
+    }
+
+    pub fn stats(&self) -> MemoStats {
+        MemoStats {
+            len: self.cache.len(),
+            ..self.stats
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.order.clear();
+        self.stats = MemoStats::default();
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Default for Memo<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn fib(memo: &mut Memo<u64, u64>, n: u64) -> u64 {
+        if n < 2 {
+            return n;
+        }
+        memo.get_or_insert_with(n, |memo| fib(memo, n - 1) + fib(memo, n - 2))
+    }
+
+    #[test]
+    fn test_recursion() {
+        let mut memo = Memo::new();
+        assert_eq!(fib(&mut memo, 90), 2880067194370816120);
+        let stats = memo.stats();
+        assert_eq!((stats.misses, stats.hits, stats.len), (89, 87, 89));
+    }
+
+    #[test]
+    fn test_capacity() {
+        let mut memo = Memo::with_capacity(2);
+        for key in [1, 2, 3, 1] {
+            memo.get_or_insert_with(key, |_| key * 10);
+        }
+        let stats = memo.stats();
+        assert_eq!((stats.hits, stats.misses, stats.evictions), (0, 4, 2));
+        assert_eq!(stats.len, 2);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut memo = Memo::new();
+        memo.get_or_insert_with(1, |_| 1);
+        memo.clear();
+        assert_eq!(memo.stats(), MemoStats::default());
+    }
+}
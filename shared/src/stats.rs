@@ -0,0 +1,93 @@
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Counters cost a single relaxed load until `init` sees `--stats`, so they
+// can stay in hot loops.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static COUNTERS: RefCell<Vec<(&'static str, u64)>> = const { RefCell::new(Vec::new()) };
+}
+
+pub fn init() -> bool {
+    let enabled = std::env::args().any(|arg| arg == "--stats");
+    ENABLED.store(enabled, Ordering::Relaxed);
+    enabled
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+pub fn bump(name: &'static str) {
+    add(name, 1);
+}
+
+pub fn add(name: &'static str, n: u64) {
+    if !enabled() {
+        return;
+    }
+    COUNTERS.with(|counters| {
+        let mut counters = counters.borrow_mut();
+        match counters.iter_mut().find(|(counter, _)| *counter == name) {
+            Some((_, count)) => *count += n,
+            None => counters.push((name, n)),
+        }
+    });
+}
+
+// Returns the counters in the order they were first bumped and resets them.
+pub fn take() -> Vec<(&'static str, u64)> {
+    COUNTERS.with(|counters| counters.take())
+}
+
+pub fn table(counters: &[(&'static str, u64)]) -> String {
+    let width = counters
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0);
+    let values: Vec<_> = counters.iter().map(|(_, n)| n.to_string()).collect();
+    let value_width = values.iter().map(String::len).max().unwrap_or(0);
+    counters
+        .iter()
+        .zip(values)
+        .map(|((name, _), value)| format!("  {:<width$}  {:>value_width$}\n", name, value))
+        .collect()
+}
+
+// Prints and resets the counters bumped since the last report, if `--stats`
+// was passed. Runners call this after each part.
+pub fn report() {
+    if !enabled() {
+        return;
+    }
+    let counters = take();
+    if !counters.is_empty() {
+        print!("{}", table(&counters));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_counters() {
+        ENABLED.store(true, Ordering::Relaxed);
+        bump("pushes");
+        add("pops", 3);
+        bump("pushes");
+        assert_eq!(take(), vec![("pushes", 2), ("pops", 3)]);
+        assert_eq!(take(), vec![]);
+    }
+
+    #[test]
+    fn test_table() {
+        assert_eq!(
+            table(&[("heap pushes", 12), ("pops", 3)]),
+            "  heap pushes  12\n  pops          3\n"
+        );
+    }
+}